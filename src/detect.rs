@@ -0,0 +1,425 @@
+//! Detection of input format by its content.
+
+use std::fmt;
+
+use crate::Format;
+
+/// How sure the detection is about the guessed format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Guessed format of the content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Detection {
+    pub format: Format,
+    pub confidence: Confidence,
+}
+
+impl Detection {
+    fn new(format: Format, confidence: Confidence) -> Self {
+        Self { format, confidence }
+    }
+}
+
+/// Guesses format of the content by its leading bytes. `complete` tells
+/// whether `bytes` is the whole input or just its beginning.
+pub fn sniff(bytes: &[u8], complete: bool) -> Option<Detection> {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(x) => Some(x),
+        Err(x) if !complete && x.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..x.valid_up_to()]).ok()
+        }
+        Err(_) => None,
+    };
+    let text = text.filter(|x| {
+        !x.chars()
+            .any(|c| c.is_control() && !c.is_ascii_whitespace())
+    });
+    match text {
+        Some(text) => sniff_text(text.trim_start_matches('\u{feff}'), complete),
+        None => sniff_protobuf(bytes, complete).map(|x| Detection::new(Format::Protobuf, x)),
+    }
+}
+
+fn sniff_text(text: &str, complete: bool) -> Option<Detection> {
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if trimmed.starts_with('{') {
+        return Some(sniff_json(text, complete));
+    }
+    if trimmed.starts_with("---") {
+        return Some(Detection::new(Format::Yaml, Confidence::High));
+    }
+
+    let mut toml_lines = 0;
+    let mut yaml_lines = 0;
+    let mut properties_lines = 0;
+    let mut tables = 0;
+    let mut array_tables = 0;
+    let mut subsections = 0;
+    let mut ini_comments = 0;
+    let mut other_lines = 0;
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        if !complete && lines.peek().is_none() {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with(';') {
+            ini_comments += 1;
+        } else if line.starts_with('!') {
+            properties_lines += 1;
+        } else if is_toml_table(line) {
+            tables += 1;
+            if line.starts_with("[[") {
+                array_tables += 1;
+            }
+        } else if is_ini_subsection(line) {
+            subsections += 1;
+        } else if line.starts_with("- ") || line == "-" {
+            yaml_lines += 1;
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            if !is_bare_key(key) && !is_quoted(key) {
+                other_lines += 1;
+            } else if is_toml_value(value.trim()) {
+                toml_lines += 1;
+            } else {
+                properties_lines += 1;
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            if is_bare_key(key.trim_end()) && (value.is_empty() || value.starts_with(' ')) {
+                yaml_lines += 1;
+            } else {
+                other_lines += 1;
+            }
+        } else {
+            other_lines += 1;
+        }
+    }
+
+    if trimmed.starts_with('[') && tables == 0 && subsections == 0 {
+        return Some(sniff_json(text, complete));
+    }
+    let confidence = |matched: usize| {
+        if other_lines == 0 && matched > 1 {
+            Confidence::High
+        } else if matched > other_lines {
+            Confidence::Medium
+        } else {
+            Confidence::Low
+        }
+    };
+    // Sections are shared by TOML and INI: quoted subsections, `;` comments
+    // and values that are not valid in TOML tell INI apart.
+    let sections = tables + subsections;
+    if sections > 0 && yaml_lines == 0 {
+        let is_ini =
+            array_tables == 0 && (subsections > 0 || ini_comments > 0 || properties_lines > 0);
+        let format = if is_ini { Format::Ini } else { Format::Toml };
+        return Some(Detection::new(
+            format,
+            confidence(sections + toml_lines + properties_lines),
+        ));
+    }
+    let best = toml_lines.max(yaml_lines).max(properties_lines);
+    if best == 0 {
+        return None;
+    }
+    if yaml_lines == best {
+        Some(Detection::new(Format::Yaml, confidence(yaml_lines)))
+    } else if properties_lines == 0 {
+        Some(Detection::new(Format::Toml, confidence(toml_lines)))
+    } else {
+        Some(Detection::new(
+            Format::JavaProperties,
+            confidence(properties_lines + toml_lines),
+        ))
+    }
+}
+
+fn sniff_json(text: &str, complete: bool) -> Detection {
+    if let Some(confidence) = sniff_jsonl(text, complete) {
+        return Detection::new(Format::Jsonl, confidence);
+    }
+    let confidence = if !complete {
+        Confidence::Medium
+    } else if serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() {
+        Confidence::High
+    } else {
+        Confidence::Low
+    };
+    Detection::new(Format::Json, confidence)
+}
+
+fn sniff_jsonl(text: &str, complete: bool) -> Option<Confidence> {
+    let mut lines: Vec<&str> = text.lines().filter(|x| !x.trim().is_empty()).collect();
+    if !complete {
+        lines.pop();
+    }
+    if lines.len() < 2 {
+        return None;
+    }
+    let documents = lines
+        .iter()
+        .all(|x| serde_json::from_str::<serde::de::IgnoredAny>(x).is_ok());
+    documents.then_some(if complete {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    })
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn is_quoted(key: &str) -> bool {
+    key.len() > 1
+        && ((key.starts_with('"') && key.ends_with('"'))
+            || (key.starts_with('\'') && key.ends_with('\'')))
+}
+
+fn is_toml_table(line: &str) -> bool {
+    let inner = line
+        .strip_prefix("[[")
+        .and_then(|x| x.strip_suffix("]]"))
+        .or_else(|| line.strip_prefix('[').and_then(|x| x.strip_suffix(']')));
+    inner.is_some_and(|x| {
+        x.split('.')
+            .all(|x| is_bare_key(x.trim()) || is_quoted(x.trim()))
+    })
+}
+
+/// Checks for INI section with quoted subsection, like `[remote "origin"]`.
+fn is_ini_subsection(line: &str) -> bool {
+    let inner = line.strip_prefix('[').and_then(|x| x.strip_suffix(']'));
+    inner
+        .and_then(|x| x.split_once(char::is_whitespace))
+        .is_some_and(|(section, subsection)| {
+            is_bare_key(section)
+                && is_quoted(subsection.trim())
+                && subsection.trim().starts_with('"')
+        })
+}
+
+fn is_toml_value(value: &str) -> bool {
+    if value.is_empty() {
+        return false;
+    }
+    if value.starts_with(['"', '\'', '[', '{']) {
+        return true;
+    }
+    if matches!(value, "true" | "false" | "inf" | "-inf" | "nan") {
+        return true;
+    }
+    let value = value.split_once(" #").map_or(value, |x| x.0).trim_end();
+    format!("x = {}", value).parse::<toml::Table>().is_ok()
+}
+
+fn sniff_protobuf(bytes: &[u8], complete: bool) -> Option<Confidence> {
+    fn read_varint(bytes: &[u8], position: &mut usize) -> Option<u64> {
+        let mut result = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *bytes.get(*position)?;
+            *position += 1;
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    if bytes.is_empty() {
+        return None;
+    }
+    let mut position = 0;
+    let mut fields = 0;
+    while position < bytes.len() {
+        let Some(key) = read_varint(bytes, &mut position) else {
+            return (!complete && fields > 0).then_some(Confidence::Low);
+        };
+        if key >> 3 == 0 || key >> 3 > 536_870_911 {
+            return None;
+        }
+        let end = match key & 7 {
+            0 => {
+                if read_varint(bytes, &mut position).is_none() {
+                    return (!complete && fields > 0).then_some(Confidence::Low);
+                }
+                position
+            }
+            1 => position + 8,
+            2 => {
+                let Some(length) = read_varint(bytes, &mut position) else {
+                    return (!complete && fields > 0).then_some(Confidence::Low);
+                };
+                position.checked_add(usize::try_from(length).ok()?)?
+            }
+            5 => position + 4,
+            _ => return None,
+        };
+        if end > bytes.len() {
+            return (!complete).then_some(Confidence::Low);
+        }
+        position = end;
+        fields += 1;
+    }
+    if fields > 1 {
+        Some(Confidence::Medium)
+    } else {
+        Some(Confidence::Low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Option<(Format, Confidence)> {
+        sniff(text.as_bytes(), true).map(|x| (x.format, x.confidence))
+    }
+
+    #[test]
+    fn sniff_ignores_empty_input() {
+        assert_eq!(sniff(b"", true), None);
+        assert_eq!(sniff(b"", false), None);
+        assert_eq!(detect("  \n\n"), None);
+    }
+
+    #[test]
+    fn sniff_detects_json() {
+        assert_eq!(
+            detect(r#"{"a": [1, 2]}"#),
+            Some((Format::Json, Confidence::High))
+        );
+        assert_eq!(
+            detect("\u{feff}[1, 2]"),
+            Some((Format::Json, Confidence::High))
+        );
+        assert_eq!(detect(r#"{"a": "#), Some((Format::Json, Confidence::Low)));
+        assert_eq!(
+            sniff(br#"{"a": "#, false).map(|x| x.confidence),
+            Some(Confidence::Medium)
+        );
+    }
+
+    #[test]
+    fn sniff_detects_jsonl() {
+        assert_eq!(
+            detect("{\"a\": 1}\n{\"a\": 2}\n"),
+            Some((Format::Jsonl, Confidence::High))
+        );
+        assert_eq!(
+            sniff(b"{\"a\": 1}\n{\"a\": 2}\n{\"a\"", false).map(|x| (x.format, x.confidence)),
+            Some((Format::Jsonl, Confidence::Medium))
+        );
+        assert_eq!(
+            detect("{\"a\": 1}\n"),
+            Some((Format::Json, Confidence::High))
+        );
+    }
+
+    #[test]
+    fn sniff_detects_yaml() {
+        assert_eq!(
+            detect("---\na: 1\n"),
+            Some((Format::Yaml, Confidence::High))
+        );
+        assert_eq!(
+            detect("name: x\nitems:\n  - a\n  - b\n"),
+            Some((Format::Yaml, Confidence::High))
+        );
+    }
+
+    #[test]
+    fn sniff_detects_toml() {
+        assert_eq!(
+            detect("title = \"x\"\n\n[server]\nport = 8080\n"),
+            Some((Format::Toml, Confidence::High))
+        );
+        assert_eq!(
+            detect("[[items]]\nname = \"a\"\n"),
+            Some((Format::Toml, Confidence::High))
+        );
+    }
+
+    #[test]
+    fn sniff_detects_ini() {
+        assert_eq!(
+            detect("[server]\nhost = example.com\n"),
+            Some((Format::Ini, Confidence::High))
+        );
+        assert_eq!(
+            detect(
+                "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = https://example.com/x.git\n"
+            ),
+            Some((Format::Ini, Confidence::High))
+        );
+        assert_eq!(
+            detect("; comment\n[server]\nport = 8080\n"),
+            Some((Format::Ini, Confidence::High))
+        );
+        assert_eq!(
+            detect("[server]\nport = 8080\n"),
+            Some((Format::Toml, Confidence::High))
+        );
+    }
+
+    #[test]
+    fn sniff_detects_properties() {
+        assert_eq!(
+            detect("server.host=example.com\nserver.port=8080\n"),
+            Some((Format::JavaProperties, Confidence::High))
+        );
+    }
+
+    #[test]
+    fn sniff_drops_partial_last_line() {
+        assert_eq!(
+            sniff(b"a: 1\nb: 2\nc = \"unfinished", false).map(|x| (x.format, x.confidence)),
+            Some((Format::Yaml, Confidence::High))
+        );
+        assert_eq!(
+            sniff("a: 1\nb: é".as_bytes().split_last().unwrap().1, false).map(|x| x.format),
+            Some(Format::Yaml)
+        );
+    }
+
+    #[test]
+    fn sniff_detects_protobuf() {
+        assert_eq!(
+            sniff(b"\x08\x96\x01\x12\x03abc", true).map(|x| x.format),
+            Some(Format::Protobuf)
+        );
+        assert_eq!(sniff(b"\x08\x96\x01\x07", true), None);
+        assert_eq!(
+            sniff(b"\x08\x96\x01\x12\x10abc", false).map(|x| (x.format, x.confidence)),
+            Some((Format::Protobuf, Confidence::Low))
+        );
+        assert_eq!(sniff(b"\x08\x96\x01\x12\x10abc", true), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use anyhow::anyhow;
+
+use crate::{
+    options::{self, bson::ExtendedJson},
+    value::Value,
+};
+
+/// Reads BSON documents one at a time from a stream of concatenated
+/// documents, like a `mongodump` collection file. Values are converted to
+/// Extended JSON, relaxed by default.
+pub fn read_documents<'a>(
+    input: impl Read + 'a,
+    in_options: &options::bson::InOptions,
+) -> impl Iterator<Item = Result<Value, anyhow::Error>> + 'a {
+    let extended_json = in_options.get_extended_json();
+    let mut input = BufReader::new(input);
+    std::iter::from_fn(move || {
+        match input.fill_buf() {
+            Ok([]) => return None,
+            Ok(_) => {}
+            Err(x) => return Some(Err(x.into())),
+        }
+        let result = bson::Document::from_reader(&mut input)
+            .map_err(anyhow::Error::from)
+            .and_then(|document| {
+                let document = bson::Bson::Document(document);
+                let json = match extended_json {
+                    ExtendedJson::Relaxed => document.into_relaxed_extjson(),
+                    ExtendedJson::Canonical => document.into_canonical_extjson(),
+                };
+                Ok(serde_json::from_value(json)?)
+            });
+        Some(result)
+    })
+}
+
+/// Reads BSON document, or array of documents if input has several of them.
+pub fn read(
+    input: impl Read,
+    in_options: &options::bson::InOptions,
+) -> Result<Value, anyhow::Error> {
+    let mut documents = read_documents(input, in_options).collect::<Result<Vec<_>, _>>()?;
+    if documents.len() == 1 {
+        Ok(documents.pop().unwrap())
+    } else {
+        Ok(Value::Array(documents))
+    }
+}
+
+fn to_extended_json(value: &Value) -> Result<serde_json::Value, anyhow::Error> {
+    match value {
+        Value::Bytes(_) => Ok(serde_json::json!({
+            "$binary": {
+                "base64": value.to_scalar_string().unwrap(),
+                "subType": "00",
+            }
+        })),
+        Value::Array(x) => Ok(serde_json::Value::Array(
+            x.iter().map(to_extended_json).collect::<Result<_, _>>()?,
+        )),
+        Value::Object(x) => Ok(serde_json::Value::Object(
+            x.iter()
+                .map(|(k, v)| Ok((k.to_string(), to_extended_json(v)?)))
+                .collect::<Result<_, anyhow::Error>>()?,
+        )),
+        x => Ok(serde_json::to_value(x)?),
+    }
+}
+
+fn write_document(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    let Value::Object(_) = value else {
+        return Err(anyhow!("Expected object but have {}", value.kind()));
+    };
+    let bson = bson::Bson::try_from(to_extended_json(value)?)?;
+    let Some(document) = bson.as_document() else {
+        return Err(anyhow!(
+            "Expected document but have Extended JSON value {}",
+            bson
+        ));
+    };
+    document.to_writer(&mut output)?;
+    Ok(())
+}
+
+/// Writes documents as concatenated BSON documents as they come.
+pub fn write_documents(
+    documents: impl Iterator<Item = Result<Value, anyhow::Error>>,
+    mut output: impl Write,
+) -> Result<(), anyhow::Error> {
+    for value in documents {
+        write_document(&value?, &mut output)?;
+    }
+    Ok(())
+}
+
+/// Writes object as BSON document, or array of objects as concatenated
+/// documents. Extended JSON values, canonical or relaxed, are converted to
+/// BSON types, and bytes are written as generic binary.
+pub fn write(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    match value {
+        Value::Array(x) => {
+            for value in x {
+                write_document(value, &mut output)?;
+            }
+            Ok(())
+        }
+        x => write_document(x, output),
+    }
+}
@@ -0,0 +1,95 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+
+use crate::value::Value;
+
+const TAG_KEY: &str = "$tag";
+const VALUE_KEY: &str = "$value";
+/// Standard date/time string tag.
+const DATETIME_TAG: u64 = 0;
+
+fn from_cbor(value: ciborium::Value) -> Result<Value, anyhow::Error> {
+    match value {
+        ciborium::Value::Null => Ok(Value::Null),
+        ciborium::Value::Bool(x) => Ok(Value::Bool(x)),
+        ciborium::Value::Integer(x) => Ok(Value::Integer(i128::from(x))),
+        ciborium::Value::Float(x) => Ok(Value::Float(x)),
+        ciborium::Value::Text(x) => Ok(Value::String(x)),
+        ciborium::Value::Bytes(x) => Ok(Value::Bytes(x)),
+        ciborium::Value::Array(x) => Ok(Value::Array(
+            x.into_iter().map(from_cbor).collect::<Result<_, _>>()?,
+        )),
+        ciborium::Value::Map(x) => {
+            let mut object = IndexMap::new();
+            for (k, v) in x {
+                let k = from_cbor(k)?;
+                let Some(k) = k.to_scalar_string() else {
+                    return Err(anyhow!("{} cannot be used as a key", k.kind()));
+                };
+                object.insert(k, from_cbor(v)?);
+            }
+            Ok(Value::Object(object))
+        }
+        ciborium::Value::Tag(DATETIME_TAG, x) if x.is_text() => {
+            Ok(Value::Datetime(x.into_text().unwrap()))
+        }
+        ciborium::Value::Tag(tag, x) => Ok(Value::Object(IndexMap::from([
+            (TAG_KEY.to_string(), Value::Integer(tag as i128)),
+            (VALUE_KEY.to_string(), from_cbor(*x)?),
+        ]))),
+        x => Err(anyhow!("Unsupported CBOR value {:?}", x)),
+    }
+}
+
+fn to_cbor(value: &Value) -> Result<ciborium::Value, anyhow::Error> {
+    match value {
+        Value::Null => Ok(ciborium::Value::Null),
+        Value::Bool(x) => Ok(ciborium::Value::Bool(*x)),
+        Value::Integer(x) => ciborium::value::Integer::try_from(*x)
+            .map(ciborium::Value::Integer)
+            .map_err(|_| anyhow!("Integer {} is out of range for CBOR", x)),
+        Value::Float(x) => Ok(ciborium::Value::Float(*x)),
+        Value::String(x) => Ok(ciborium::Value::Text(x.to_string())),
+        Value::Datetime(x) => Ok(ciborium::Value::Tag(
+            DATETIME_TAG,
+            Box::new(ciborium::Value::Text(x.to_string())),
+        )),
+        Value::Bytes(x) => Ok(ciborium::Value::Bytes(x.clone())),
+        Value::Array(x) => Ok(ciborium::Value::Array(
+            x.iter().map(to_cbor).collect::<Result<_, _>>()?,
+        )),
+        Value::Object(x) => {
+            if let (2, Some(Value::Integer(tag)), Some(value)) =
+                (x.len(), x.get(TAG_KEY), x.get(VALUE_KEY))
+            {
+                let tag =
+                    u64::try_from(*tag).map_err(|_| anyhow!("Tag {} is out of range", tag))?;
+                return Ok(ciborium::Value::Tag(tag, Box::new(to_cbor(value)?)));
+            }
+            Ok(ciborium::Value::Map(
+                x.iter()
+                    .map(|(k, v)| Ok((ciborium::Value::Text(k.to_string()), to_cbor(v)?)))
+                    .collect::<Result<_, anyhow::Error>>()?,
+            ))
+        }
+    }
+}
+
+/// Reads CBOR value.
+///
+/// Byte strings are read as bytes, non-string map keys are converted to
+/// strings, date/time strings (tag 0) become datetimes and other tagged
+/// values become objects with `$tag` number and tagged `$value`.
+pub fn read(input: impl Read) -> Result<Value, anyhow::Error> {
+    let value: ciborium::Value = ciborium::from_reader(input)?;
+    from_cbor(value)
+}
+
+/// Writes document as CBOR value. Datetimes are written with tag 0, objects
+/// with `$tag` and `$value` keys are written as tagged values.
+pub fn write(value: &Value, output: impl Write) -> Result<(), anyhow::Error> {
+    ciborium::into_writer(&to_cbor(value)?, output)?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexSet;
+
+use crate::{options, value::Value};
+
+fn infer_types(value: Value) -> Value {
+    match value {
+        Value::String(x) => {
+            if x.is_empty() {
+                Value::Null
+            } else if let Ok(x) = x.parse::<bool>() {
+                Value::Bool(x)
+            } else if let Ok(x) = x.parse::<i128>() {
+                Value::Integer(x)
+            } else if let Some(x) = x
+                .parse::<f64>()
+                .ok()
+                .filter(|_| x.contains(|c: char| c.is_ascii_digit()))
+            {
+                Value::Float(x)
+            } else {
+                Value::String(x)
+            }
+        }
+        Value::Object(x) => {
+            Value::Object(x.into_iter().map(|(k, v)| (k, infer_types(v))).collect())
+        }
+        x => x,
+    }
+}
+
+/// Reads rows as array of objects keyed by header. Dotted headers are
+/// unflattened into nested objects.
+pub fn read(
+    input: impl Read,
+    delimiter: u8,
+    in_options: &options::csv::InOptions,
+) -> Result<Value, anyhow::Error> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(input);
+    let headers = reader.headers()?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let row = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(k, v)| (k.to_string(), v.to_string()));
+        let row = Value::unflatten(row, ".")?;
+        if in_options.get_infer_types() {
+            rows.push(infer_types(row));
+        } else {
+            rows.push(row);
+        }
+    }
+    Ok(Value::Array(rows))
+}
+
+/// Writes array of objects as rows. Nested objects are flattened into dotted
+/// headers, header is the union of keys of all rows.
+pub fn write(value: &Value, output: impl Write, delimiter: u8) -> Result<(), anyhow::Error> {
+    let rows = match value {
+        Value::Array(x) => x.iter().collect(),
+        Value::Object(_) => vec![value],
+        x => return Err(anyhow!("Expected array of objects but have {}", x.kind())),
+    };
+    let mut flat_rows = Vec::new();
+    let mut headers = IndexSet::new();
+    for row in rows {
+        if !matches!(row, Value::Object(_)) {
+            return Err(anyhow!("Expected row to be object but have {}", row.kind()));
+        }
+        let row = row.flatten(".")?;
+        headers.extend(row.keys().cloned());
+        flat_rows.push(row);
+    }
+    let mut writer = ::csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(output);
+    writer.write_record(&headers)?;
+    for row in flat_rows {
+        writer.write_record(
+            headers
+                .iter()
+                .map(|x| row.get(x).map(|x| x.as_str()).unwrap_or("")),
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
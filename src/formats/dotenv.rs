@@ -0,0 +1,254 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+
+use crate::{options, value::Value};
+
+const SEPARATOR: &str = "__";
+
+fn expand(
+    value: &str,
+    escapes: bool,
+    variables: &IndexMap<String, Value>,
+) -> Result<String, anyhow::Error> {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if escapes => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some('t') => result.push('\t'),
+                Some(x) => result.push(x),
+                None => result.push('\\'),
+            },
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut reference = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(x) => reference.push(x),
+                        None => return Err(anyhow!("Unclosed variable reference in {}", value)),
+                    }
+                }
+                let (name, default) = match reference.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (reference.as_str(), None),
+                };
+                match variables.get(name) {
+                    Some(Value::String(x)) => result.push_str(x),
+                    _ => match std::env::var(name) {
+                        Ok(x) => result.push_str(&x),
+                        Err(_) => result.push_str(default.unwrap_or_default()),
+                    },
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+/// Reads dotenv file into flat object of string values.
+///
+/// Lines may start with `export `. Values in single quotes are taken
+/// literally, values in double quotes may span several lines and support
+/// escapes. `${VAR}` references in unquoted and double-quoted values are
+/// expanded from variables defined above in the file or from the environment,
+/// `${VAR:-default}` falls back to the default if neither has it.
+pub fn read(mut input: impl Read) -> Result<Value, anyhow::Error> {
+    let mut dotenv = String::new();
+    input.read_to_string(&mut dotenv)?;
+    let mut variables = IndexMap::new();
+    let mut lines = dotenv.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(anyhow!("Expected KEY=value at line {}", number + 1));
+        };
+        let key = key.trim_end();
+        let value = value.trim_start();
+        let value = if let Some(value) = value.strip_prefix('\'') {
+            let Some((value, _)) = value.split_once('\'') else {
+                return Err(anyhow!("Unclosed single quote at line {}", number + 1));
+            };
+            value.to_string()
+        } else if let Some(value) = value.strip_prefix('"') {
+            let mut value = value.to_string();
+            loop {
+                let mut escaped = false;
+                let end = value.char_indices().find_map(|(i, c)| {
+                    let found = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    found.then_some(i)
+                });
+                if let Some(end) = end {
+                    value.truncate(end);
+                    break;
+                }
+                let Some((_, line)) = lines.next() else {
+                    return Err(anyhow!("Unclosed double quote at line {}", number + 1));
+                };
+                value.push('\n');
+                value.push_str(line);
+            }
+            expand(&value, true, &variables)?
+        } else {
+            let value = value.split_once(" #").map_or(value, |x| x.0).trim_end();
+            expand(value, false, &variables)?
+        };
+        variables.insert(key.to_string(), Value::String(value));
+    }
+    Ok(Value::Object(variables))
+}
+
+/// Converts key to upper snake case: `serverPort` and `server-port` both
+/// become `SERVER_PORT`.
+fn to_upper_snake(key: &str) -> String {
+    let mut result = String::new();
+    let mut previous: Option<char> = None;
+    for c in key.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && previous.is_some_and(|x| x.is_lowercase() || x.is_numeric()) {
+                result.push('_');
+            }
+            result.extend(c.to_uppercase());
+        } else {
+            result.push('_');
+        }
+        previous = Some(c);
+    }
+    result
+}
+
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_alphanumeric() || "_-.,:/@+".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Writes document as dotenv file, flattening nested objects into upper snake
+/// keys joined by separator (`__` by default).
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::dotenv::OutOptions,
+) -> Result<(), anyhow::Error> {
+    let separator = out_options.get_separator().unwrap_or(SEPARATOR);
+    for (key, value) in value.flatten_with(separator, to_upper_snake)? {
+        writeln!(output, "{}={}", key, quote(&value))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::value::json;
+
+    fn read_str(dotenv: &str) -> Value {
+        read(dotenv.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn expand_references() {
+        let variables = IndexMap::from([("A".to_string(), Value::String("1".to_string()))]);
+        assert_eq!(expand("${A}-x", false, &variables).unwrap(), "1-x");
+        assert_eq!(
+            expand("${CVTO_TEST_UNSET:-fallback}", false, &variables).unwrap(),
+            "fallback"
+        );
+        assert_eq!(expand("${CVTO_TEST_UNSET}", false, &variables).unwrap(), "");
+        assert_eq!(expand("$A", false, &variables).unwrap(), "$A");
+        assert!(expand("${A", false, &variables).is_err());
+    }
+
+    #[test]
+    fn expand_escapes() {
+        let variables = IndexMap::new();
+        assert_eq!(expand(r"a\nb\t\\", true, &variables).unwrap(), "a\nb\t\\");
+        assert_eq!(expand(r"\${A}", true, &variables).unwrap(), "${A}");
+        assert_eq!(expand(r"a\nb", false, &variables).unwrap(), r"a\nb");
+    }
+
+    #[test]
+    fn read_handles_quotes_and_comments() {
+        assert_eq!(
+            read_str(
+                "# comment\nexport A=1 # inline\nB='${A} \\n'\nC=\"${A}\\n\\\"q\\\"\"\nD=\"multi\nline\"\nE=\n"
+            ),
+            json(r#"{"A": "1", "B": "${A} \\n", "C": "1\n\"q\"", "D": "multi\nline", "E": ""}"#)
+        );
+        assert!(read("A='x\n".as_bytes()).is_err());
+        assert!(read("A=\"x\n".as_bytes()).is_err());
+        assert!(read("A\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn to_upper_snake_splits_words() {
+        assert_eq!(to_upper_snake("serverPort"), "SERVER_PORT");
+        assert_eq!(to_upper_snake("server-port"), "SERVER_PORT");
+        assert_eq!(to_upper_snake("server.port"), "SERVER_PORT");
+        assert_eq!(to_upper_snake("HTTPServer"), "HTTPSERVER");
+        assert_eq!(to_upper_snake("v2Api"), "V2_API");
+    }
+
+    #[test]
+    fn quote_only_when_needed() {
+        assert_eq!(quote("plain-value_1.0:/@+"), "plain-value_1.0:/@+");
+        assert_eq!(quote(""), "");
+        assert_eq!(quote("a b"), "\"a b\"");
+        assert_eq!(quote("a\"$\\\n"), r#""a\"\$\\\n""#);
+    }
+
+    #[test]
+    fn write_rejects_keys_that_become_the_same() {
+        let value = json(r#"{"server-port": 1, "serverPort": 2}"#);
+        assert!(write(&value, Vec::new(), &options::dotenv::OutOptions::new()).is_err());
+    }
+
+    #[test]
+    fn write_round_trips() {
+        let value = json(r#"{"server": {"hostName": "a b", "port": 80}, "note": "${x}\n\"q\""}"#);
+        let mut dotenv = Vec::new();
+        write(&value, &mut dotenv, &options::dotenv::OutOptions::new()).unwrap();
+        assert_eq!(
+            read(dotenv.as_slice()).unwrap(),
+            json(r#"{"SERVER__HOST_NAME": "a b", "SERVER__PORT": "80", "NOTE": "${x}\n\"q\""}"#)
+        );
+        let mut out_options = options::dotenv::OutOptions::new();
+        out_options.set(options::dotenv::OutOption::Separator("_".to_string()));
+        let mut dotenv = Vec::new();
+        write(&value, &mut dotenv, &out_options).unwrap();
+        assert!(
+            String::from_utf8(dotenv)
+                .unwrap()
+                .starts_with("SERVER_HOST_NAME=")
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use anyhow::anyhow;
+use hcl::{
+    Attribute, Block, BlockLabel, Body, Expression, Identifier, Structure,
+    eval::{Context, Evaluate},
+};
+use indexmap::IndexMap;
+
+use crate::{options, value::Value};
+
+fn from_hcl(value: hcl::Value) -> Value {
+    match value {
+        hcl::Value::Null => Value::Null,
+        hcl::Value::Bool(x) => Value::Bool(x),
+        hcl::Value::Number(x) => {
+            if let Some(x) = x.as_i64() {
+                Value::Integer(x as i128)
+            } else if let Some(x) = x.as_u64() {
+                Value::Integer(x as i128)
+            } else {
+                Value::Float(x.as_f64().unwrap())
+            }
+        }
+        hcl::Value::String(x) => Value::String(x),
+        hcl::Value::Array(x) => Value::Array(x.into_iter().map(from_hcl).collect()),
+        hcl::Value::Object(x) => {
+            Value::Object(x.into_iter().map(|(k, v)| (k, from_hcl(v))).collect())
+        }
+    }
+}
+
+fn read_body(
+    body: Body,
+    path: &str,
+    context: &Context,
+) -> Result<IndexMap<String, Value>, anyhow::Error> {
+    let mut result = IndexMap::new();
+    for structure in body {
+        match structure {
+            Structure::Attribute(x) => {
+                let key = format!("{}{}", path, x.key);
+                let value = x.expr.evaluate(context).map_err(|e| {
+                    anyhow!(
+                        "Expression of attribute {} cannot be evaluated statically: {}",
+                        key,
+                        e
+                    )
+                })?;
+                result.insert(x.key.into_inner(), from_hcl(value));
+            }
+            Structure::Block(x) => {
+                let mut keys = vec![x.identifier.into_inner()];
+                keys.extend(x.labels.into_iter().map(|x| x.into_inner()));
+                let path = format!("{}{}.", path, keys.join("."));
+                let body = Value::Object(read_body(x.body, &path, context)?);
+                let (last, parents) = keys.split_last().unwrap();
+                let mut object = &mut result;
+                for key in parents {
+                    let child = object
+                        .entry(key.to_string())
+                        .or_insert_with(|| Value::Object(IndexMap::new()));
+                    object = match child {
+                        Value::Object(x) => x,
+                        _ => {
+                            return Err(anyhow!(
+                                "Block {} conflicts with attribute {}",
+                                path.trim_end_matches('.'),
+                                key
+                            ));
+                        }
+                    };
+                }
+                match object.get_mut(last) {
+                    Some(Value::Array(x)) => x.push(body),
+                    Some(x) => {
+                        let first = std::mem::replace(x, Value::Null);
+                        *x = Value::Array(vec![first, body]);
+                    }
+                    None => {
+                        object.insert(last.to_string(), body);
+                    }
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Reads HCL file. Attributes become keys, blocks become objects nested under
+/// their type and labels, so `resource "a" "b" {}` is read as
+/// `{"resource": {"a": {"b": {}}}}`. Repeated blocks are collected into
+/// arrays. Expressions are evaluated without any variables or functions, so
+/// references like `var.name` are reported as errors.
+pub fn read(mut input: impl Read) -> Result<Value, anyhow::Error> {
+    let mut hcl = String::new();
+    input.read_to_string(&mut hcl)?;
+    let body = hcl::parse(&hcl)?;
+    Ok(Value::Object(read_body(body, "", &Context::new())?))
+}
+
+/// Parses block names like `resource:2` into number of label levels by name.
+fn parse_blocks(blocks: Vec<String>) -> Result<HashMap<String, usize>, anyhow::Error> {
+    let mut result = HashMap::new();
+    for block in blocks {
+        let (name, labels) = match block.split_once(':') {
+            Some((name, labels)) => (
+                name,
+                labels
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid number of labels in block {}", block))?,
+            ),
+            None => (block.as_str(), 0),
+        };
+        result.insert(name.to_string(), labels);
+    }
+    Ok(result)
+}
+
+fn write_blocks(
+    body: &mut Vec<Structure>,
+    identifier: &Identifier,
+    labels: &[String],
+    levels: usize,
+    value: &Value,
+    blocks: &HashMap<String, usize>,
+) -> Result<(), anyhow::Error> {
+    match value {
+        Value::Array(x) => {
+            for value in x {
+                write_blocks(body, identifier, labels, levels, value, blocks)?;
+            }
+        }
+        Value::Object(x) if levels == 0 => {
+            body.push(Structure::Block(Block {
+                identifier: identifier.clone(),
+                labels: labels.iter().cloned().map(BlockLabel::String).collect(),
+                body: write_body(x, blocks)?,
+            }));
+        }
+        Value::Object(x) => {
+            for (label, value) in x {
+                let mut labels = labels.to_vec();
+                labels.push(label.to_string());
+                write_blocks(body, identifier, &labels, levels - 1, value, blocks)?;
+            }
+        }
+        x => {
+            let mut path = vec![identifier.as_str()];
+            path.extend(labels.iter().map(|x| x.as_str()));
+            return Err(anyhow!(
+                "Block {} must be an object but have {}",
+                path.join("."),
+                x.kind()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn write_body(
+    object: &IndexMap<String, Value>,
+    blocks: &HashMap<String, usize>,
+) -> Result<Body, anyhow::Error> {
+    let mut body = Vec::new();
+    for (key, value) in object {
+        let identifier = Identifier::new(key.as_str())
+            .map_err(|_| anyhow!("Key {} is not a valid HCL identifier", key))?;
+        match blocks.get(key) {
+            Some(levels) => write_blocks(&mut body, &identifier, &[], *levels, value, blocks)?,
+            None => {
+                let expr = Expression::from(hcl::to_value(value)?);
+                body.push(Structure::Attribute(Attribute::new(identifier, expr)));
+            }
+        }
+    }
+    Ok(Body::from(body))
+}
+
+/// Writes document as HCL. Values are written as attributes, so objects
+/// become object expressions like `tags = { Name = "x" }`. Keys listed in
+/// options are written as blocks instead, taking as many nested object levels
+/// as labels as set for them: with `resource:2`,
+/// `{"resource": {"a": {"b": {}}}}` is written as `resource "a" "b" {}`.
+/// Arrays of objects become repeated blocks.
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::hcl::OutOptions,
+) -> Result<(), anyhow::Error> {
+    let Value::Object(x) = value else {
+        return Err(anyhow!("Expected object but have {}", value.kind()));
+    };
+    let blocks = parse_blocks(out_options.get_blocks())?;
+    output.write_all(hcl::to_string(&write_body(x, &blocks)?)?.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+
+    const TERRAFORM: &str = r#"
+resource "aws_instance" "web" {
+  tags = { Name = "x" }
+  ingress {
+    port = 80
+  }
+  ingress {
+    port = 443
+  }
+}
+"#;
+
+    fn write_with(value: &Value, blocks: &[&str]) -> Result<String, anyhow::Error> {
+        let mut out_options = options::hcl::OutOptions::new();
+        out_options.set(options::hcl::OutOption::Blocks(
+            blocks.iter().map(|x| x.to_string()).collect(),
+        ));
+        let mut hcl = Vec::new();
+        write(value, &mut hcl, &out_options)?;
+        Ok(String::from_utf8(hcl).unwrap())
+    }
+
+    #[test]
+    fn write_keeps_objects_as_attributes() {
+        let value = read(TERRAFORM.as_bytes()).unwrap();
+        let hcl = write_with(&value, &[]).unwrap();
+        assert!(hcl.starts_with("resource = {"));
+        assert_eq!(read(hcl.as_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn write_restores_blocks_with_labels() {
+        let value = read(TERRAFORM.as_bytes()).unwrap();
+        let hcl = write_with(&value, &["resource:2", "ingress"]).unwrap();
+        assert!(hcl.starts_with("resource \"aws_instance\" \"web\" {"));
+        assert!(hcl.contains("ingress {"));
+        assert_eq!(read(hcl.as_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn write_rejects_invalid_blocks() {
+        let value = read(TERRAFORM.as_bytes()).unwrap();
+        assert!(write_with(&value, &["resource:x"]).is_err());
+        assert!(write_with(&value, &["resource:4"]).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+
+use crate::{
+    options::{
+        self,
+        ini::{DuplicateKeys, Nesting, Subsections},
+    },
+    value::Value,
+};
+
+const COMMENT_CHARS: &str = ";#";
+
+fn parse_section(name: &str, subsections: Subsections) -> Result<Vec<String>, anyhow::Error> {
+    let name = name.trim();
+    let (section, subsection) = match name.split_once(char::is_whitespace) {
+        Some((section, subsection)) if subsections != Subsections::None => {
+            let subsection = subsection.trim();
+            let Some(subsection) = subsection
+                .strip_prefix('"')
+                .and_then(|x| x.strip_suffix('"'))
+            else {
+                return Err(anyhow!("Subsection name must be quoted in [{}]", name));
+            };
+            let subsection = subsection.replace("\\\"", "\"").replace("\\\\", "\\");
+            (section, Some(subsection))
+        }
+        _ => (name, None),
+    };
+    let mut path: Vec<String> = if subsections == Subsections::Dotted {
+        section.split('.').map(|x| x.to_string()).collect()
+    } else {
+        vec![section.to_string()]
+    };
+    path.extend(subsection);
+    Ok(path)
+}
+
+fn get_section<'a>(
+    root: &'a mut IndexMap<String, Value>,
+    path: &[String],
+) -> Result<&'a mut IndexMap<String, Value>, anyhow::Error> {
+    let mut section = root;
+    for name in path {
+        let child = section
+            .entry(name.to_string())
+            .or_insert_with(|| Value::Object(IndexMap::new()));
+        section = match child {
+            Value::Object(x) => x,
+            _ => {
+                return Err(anyhow!(
+                    "Section [{}] conflicts with key {}",
+                    path.join("."),
+                    name
+                ));
+            }
+        };
+    }
+    Ok(section)
+}
+
+fn parse_value(value: &str, comment_chars: &str) -> String {
+    let value = value.trim();
+    if let Some(x) = value.strip_prefix('"') {
+        let mut result = String::new();
+        let mut chars = x.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return result,
+                '\\' => match chars.next() {
+                    Some(c @ ('"' | '\\')) => result.push(c),
+                    Some(c) => {
+                        result.push('\\');
+                        result.push(c);
+                    }
+                    None => break,
+                },
+                c => result.push(c),
+            }
+        }
+    }
+    if let Some(x) = value.strip_prefix('\'') {
+        if let Some((x, _)) = x.split_once('\'') {
+            return x.to_string();
+        }
+    }
+    let mut end = value.len();
+    let mut previous = None;
+    for (i, c) in value.char_indices() {
+        if comment_chars.contains(c) && previous.is_some_and(char::is_whitespace) {
+            end = i;
+            break;
+        }
+        previous = Some(c);
+    }
+    value[..end].trim_end().to_string()
+}
+
+/// Reads INI file. Sections become objects, keys before the first section
+/// are kept at the top level, all values are strings. Keys without value are
+/// read as nulls.
+pub fn read(
+    mut input: impl Read,
+    in_options: &options::ini::InOptions,
+) -> Result<Value, anyhow::Error> {
+    let comment_chars = in_options.get_comment_chars().unwrap_or(COMMENT_CHARS);
+    let duplicate_keys = in_options.get_duplicate_keys();
+    let subsections = in_options.get_subsections();
+    let mut ini = String::new();
+    input.read_to_string(&mut ini)?;
+    let mut root = IndexMap::new();
+    let mut path = Vec::new();
+    for (number, line) in ini.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(|x| comment_chars.contains(x)) {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            let Some((name, _)) = name.rsplit_once(']') else {
+                return Err(anyhow!("Unclosed section name at line {}", number + 1));
+            };
+            path = parse_section(name, subsections)?;
+            get_section(&mut root, &path)?;
+            continue;
+        }
+        let (key, value) = match line.find(['=', ':']) {
+            Some(i) => (
+                line[..i].trim_end(),
+                Value::String(parse_value(&line[i + 1..], comment_chars)),
+            ),
+            None => (line, Value::Null),
+        };
+        let section = get_section(&mut root, &path)?;
+        match (section.get_mut(key), duplicate_keys) {
+            (None, _) | (Some(_), DuplicateKeys::Last) => {
+                section.insert(key.to_string(), value);
+            }
+            (Some(_), DuplicateKeys::First) => {}
+            (Some(_), DuplicateKeys::Error) => {
+                return Err(anyhow!("Duplicate key {} at line {}", key, number + 1));
+            }
+            (Some(Value::Array(x)), DuplicateKeys::Array) => x.push(value),
+            (Some(x), DuplicateKeys::Array) => {
+                let first = std::mem::replace(x, Value::Null);
+                *x = Value::Array(vec![first, value]);
+            }
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+/// Wraps value in double quotes, escaping quotes and backslashes the way
+/// `parse_value` and `parse_section` read them.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Writes document as INI. Top-level objects become sections, other
+/// top-level values are written before the first section. Objects nested in
+/// sections become subsections like `[remote "origin"]`, objects nested
+/// deeper are rejected or flattened into dotted keys depending on options.
+/// Nulls are written as keys without value. Keys and section names that
+/// would be read back differently are rejected.
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::ini::OutOptions,
+) -> Result<(), anyhow::Error> {
+    fn format_value(key: &str, value: &Value) -> Result<Option<String>, anyhow::Error> {
+        let value = match value {
+            Value::Null => return Ok(None),
+            Value::Array(x) => {
+                let mut result = Vec::new();
+                for value in x {
+                    match value.to_scalar_string() {
+                        Some(value) => result.push(value),
+                        None => {
+                            return Err(anyhow!(
+                                "Array under key {} cannot contain {} values",
+                                key,
+                                value.kind()
+                            ));
+                        }
+                    }
+                }
+                result.join(",")
+            }
+            x => x.to_scalar_string().unwrap(),
+        };
+        if value.contains('\n') {
+            return Err(anyhow!("Value of key {} cannot contain line breaks", key));
+        }
+        if value.trim() != value || value.contains([';', '#', '"']) || value.starts_with('\'') {
+            Ok(Some(quote(&value)))
+        } else {
+            Ok(Some(value))
+        }
+    }
+
+    fn write_entries(
+        output: &mut impl Write,
+        entries: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<(), anyhow::Error> {
+        for (key, value) in entries {
+            if key.is_empty()
+                || key.trim() != key
+                || key.contains(['=', ':', '\n', '\r'])
+                || key.starts_with(|x| x == '[' || COMMENT_CHARS.contains(x))
+            {
+                return Err(anyhow!("Key {:?} cannot be written to INI", key));
+            }
+            match format_value(&key, &value)? {
+                Some(value) => writeln!(output, "{} = {}", key, value)?,
+                None => writeln!(output, "{}", key)?,
+            }
+        }
+        Ok(())
+    }
+
+    let Value::Object(root) = value else {
+        return Err(anyhow!("Expected object but have {}", value.kind()));
+    };
+    fn flatten_entries(
+        path: &str,
+        object: &IndexMap<String, Value>,
+        nesting: Nesting,
+    ) -> Result<Vec<(String, Value)>, anyhow::Error> {
+        let mut entries = Vec::new();
+        for (key, value) in object {
+            match (value, nesting) {
+                (Value::Object(_), Nesting::Reject) => {
+                    return Err(anyhow!(
+                        "Key {}.{} is nested deeper than section and key",
+                        path,
+                        key
+                    ));
+                }
+                (Value::Object(_), Nesting::Flatten) => {
+                    for (k, v) in value.flatten(".")? {
+                        entries.push((format!("{}.{}", key, k), Value::String(v)));
+                    }
+                }
+                (value, _) => entries.push((key.to_string(), value.clone())),
+            }
+        }
+        Ok(entries)
+    }
+
+    let nesting = out_options.get_nesting();
+    let (sections, globals): (Vec<_>, Vec<_>) = root
+        .iter()
+        .partition(|(_, v)| matches!(v, Value::Object(_)));
+    let mut blocks = Vec::new();
+    for (name, section) in sections {
+        let Value::Object(section) = section else {
+            unreachable!()
+        };
+        if name.is_empty() || name.contains(|x: char| x.is_whitespace() || x == ']') {
+            return Err(anyhow!("Section name {:?} cannot be written to INI", name));
+        }
+        let (subsections, keys): (IndexMap<_, _>, IndexMap<_, _>) = section
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .partition(|(_, v)| matches!(v, Value::Object(_)));
+        if !keys.is_empty() || subsections.is_empty() {
+            blocks.push((
+                format!("[{}]", name),
+                flatten_entries(name, &keys, nesting)?,
+            ));
+        }
+        for (subsection, value) in subsections {
+            let Value::Object(value) = value else {
+                unreachable!()
+            };
+            if subsection.contains(['\n', '\r']) {
+                return Err(anyhow!(
+                    "Subsection name {:?} cannot be written to INI",
+                    subsection
+                ));
+            }
+            blocks.push((
+                format!("[{} {}]", name, quote(&subsection)),
+                flatten_entries(&format!("{}.{}", name, subsection), &value, nesting)?,
+            ));
+        }
+    }
+    write_entries(
+        &mut output,
+        globals.into_iter().map(|(k, v)| (k.to_string(), v.clone())),
+    )?;
+    for (i, (header, entries)) in blocks.into_iter().enumerate() {
+        if i > 0 || !root.values().all(|x| matches!(x, Value::Object(_))) {
+            writeln!(output)?;
+        }
+        writeln!(output, "{}", header)?;
+        write_entries(&mut output, entries)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Options;
+    use crate::value::json;
+
+    fn read_with(ini: &str, option: Option<options::ini::InOption>) -> Value {
+        let mut in_options = options::ini::InOptions::new();
+        if let Some(option) = option {
+            in_options.set(option);
+        }
+        read(ini.as_bytes(), &in_options).unwrap()
+    }
+
+    fn round_trip(value: &Value) -> Value {
+        let mut ini = Vec::new();
+        write(value, &mut ini, &options::ini::OutOptions::new()).unwrap();
+        read(ini.as_slice(), &options::ini::InOptions::new()).unwrap()
+    }
+
+    #[test]
+    fn parse_section_splits_subsections() {
+        assert_eq!(
+            parse_section(r#"remote "origin""#, Subsections::Quoted).unwrap(),
+            ["remote", "origin"]
+        );
+        assert_eq!(
+            parse_section(r#"a "b \"c\"""#, Subsections::Quoted).unwrap(),
+            ["a", r#"b "c""#]
+        );
+        assert_eq!(parse_section("a.b", Subsections::Quoted).unwrap(), ["a.b"]);
+        assert_eq!(
+            parse_section("a.b", Subsections::Dotted).unwrap(),
+            ["a", "b"]
+        );
+        assert_eq!(
+            parse_section(r#"a "b""#, Subsections::None).unwrap(),
+            [r#"a "b""#]
+        );
+        assert!(parse_section("a b", Subsections::Quoted).is_err());
+    }
+
+    #[test]
+    fn parse_value_handles_quotes_and_comments() {
+        assert_eq!(parse_value(" a b ", ";#"), "a b");
+        assert_eq!(parse_value("a ; comment", ";#"), "a");
+        assert_eq!(parse_value("a;b", ";#"), "a;b");
+        assert_eq!(parse_value(r#""a ; b" ; comment"#, ";#"), "a ; b");
+        assert_eq!(parse_value(r#""a\"b\\c""#, ";#"), r#"a"b\c"#);
+        assert_eq!(parse_value(r"'a\'", ";#"), r"a\");
+        assert_eq!(parse_value(r"C:\dir", ";#"), r"C:\dir");
+        assert_eq!(parse_value(r#""unclosed"#, ";#"), r#""unclosed"#);
+    }
+
+    #[test]
+    fn read_applies_duplicate_keys_policy() {
+        let ini = "[s]\nk = 1\nk = 2\nk = 3\n";
+        let option = |x| Some(options::ini::InOption::DuplicateKeys(x));
+        assert_eq!(read_with(ini, None), json(r#"{"s": {"k": "3"}}"#));
+        assert_eq!(
+            read_with(ini, option(DuplicateKeys::First)),
+            json(r#"{"s": {"k": "1"}}"#)
+        );
+        assert_eq!(
+            read_with(ini, option(DuplicateKeys::Array)),
+            json(r#"{"s": {"k": ["1", "2", "3"]}}"#)
+        );
+        let mut in_options = options::ini::InOptions::new();
+        in_options.set(options::ini::InOption::DuplicateKeys(DuplicateKeys::Error));
+        assert!(read(ini.as_bytes(), &in_options).is_err());
+    }
+
+    #[test]
+    fn read_keeps_globals_and_bare_keys() {
+        assert_eq!(
+            read_with("a = 1\n# comment\n[s]\nflag\n", None),
+            json(r#"{"a": "1", "s": {"flag": null}}"#)
+        );
+    }
+
+    #[test]
+    fn write_round_trips_quoted_values() {
+        let value = json(r#"{"s": {"a": "a\"b", "b": " a;b\"c", "c": "'x'", "d": "C:\\dir"}}"#);
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn write_round_trips_subsections() {
+        let value = json(
+            r#"{"core": {"bare": "false"}, "remote": {"origin": {"url": "x"}, "a \"b\"": {"url": "y"}}}"#,
+        );
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn write_rejects_names_that_cannot_be_read_back() {
+        for value in [
+            r#"{"s": {"a=b": "c"}}"#,
+            r#"{"s": {"a:b": "c"}}"#,
+            r#"{"s": {"a\nb": "c"}}"#,
+            r#"{"s": {"[a": "c"}}"#,
+            r#"{"s": {";a": "c"}}"#,
+            r##"{"s": {"#a": "c"}}"##,
+            r#"{"s": {" a": "c"}}"#,
+            r#"{"s": {"": "c"}}"#,
+            r#"{"my sec": {"k": "v"}}"#,
+            r#"{"a]b": {"k": "v"}}"#,
+            r#"{"s": {"a\nb": {"k": "v"}}}"#,
+        ] {
+            let value = json(value);
+            assert!(
+                write(&value, Vec::new(), &options::ini::OutOptions::new()).is_err(),
+                "{:?} is written",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn write_round_trips_names() {
+        let value = json(r#"{"a.b": "1", "s-1": {"k.x-y_z": "v", "k[0]": "w"}}"#);
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn write_rejects_or_flattens_deeper_nesting() {
+        let value = json(r#"{"a": {"b": {"c": {"d": "1"}}}}"#);
+        assert!(write(&value, Vec::new(), &options::ini::OutOptions::new()).is_err());
+        let mut out_options = options::ini::OutOptions::new();
+        out_options.set(options::ini::OutOption::Nesting(Nesting::Flatten));
+        let mut ini = Vec::new();
+        write(&value, &mut ini, &out_options).unwrap();
+        assert_eq!(String::from_utf8(ini).unwrap(), "[a \"b\"]\nc.d = 1\n");
+    }
+}
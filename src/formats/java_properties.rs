@@ -0,0 +1,107 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+
+use crate::{options, value::Value};
+
+/// Reads Java Properties, unflattening dotted keys into nested objects.
+pub fn read(input: impl Read) -> Result<Value, anyhow::Error> {
+    let mut properties = Vec::new();
+    java_properties::PropertiesIter::new(input)
+        .read_into(|k, v| properties.push((k, v)))
+        .map_err(|x| anyhow!("Error while reading Java Properties from file: {}", x))?;
+    Value::unflatten(properties, ".")
+}
+
+/// Writes document as Java Properties, flattening nested objects into dotted keys.
+pub fn write(
+    value: &Value,
+    output: impl Write,
+    out_options: &options::java_properties::OutOptions,
+) -> Result<(), anyhow::Error> {
+    fn write_properties(
+        value: &Value,
+        output: impl Write,
+        separator: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let mut writer = java_properties::PropertiesWriter::new(output);
+        if let Some(separator) = separator {
+            writer.set_kv_separator(separator)?;
+        }
+        for (key, value) in value.flatten(".")? {
+            writer.write(key.as_str(), value.as_str())?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    write_properties(value, output, out_options.get_kv_separator())
+        .map_err(|x| anyhow!("Error while writing Java Properties to file: {}", x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::json;
+
+    fn read_str(properties: &str) -> Value {
+        read(properties.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn read_keeps_values_of_parent_keys() {
+        let value = read_str(
+            "log4j.appender.stdout=org.apache.log4j.ConsoleAppender\nlog4j.appender.stdout.layout=org.apache.log4j.PatternLayout\n",
+        );
+        assert_eq!(
+            value,
+            json(
+                r##"{"log4j": {"appender": {"stdout": {
+                    "#value": "org.apache.log4j.ConsoleAppender",
+                    "layout": "org.apache.log4j.PatternLayout"
+                }}}}"##
+            )
+        );
+        assert_eq!(
+            read_str("a.b=2\na=1\n"),
+            json(r##"{"a": {"b": "2", "#value": "1"}}"##)
+        );
+    }
+
+    #[test]
+    fn write_round_trips_values_of_parent_keys() {
+        let properties = "a=1\na.b=2\n";
+        let mut output = Vec::new();
+        write(
+            &read_str(properties),
+            &mut output,
+            &options::java_properties::OutOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), properties);
+    }
+
+    #[test]
+    fn read_joins_continuation_lines() {
+        assert_eq!(
+            read_str("list = a, \\\n       b, \\\n       c\n"),
+            json(r#"{"list": "a, b, c"}"#)
+        );
+    }
+
+    #[test]
+    fn read_unescapes_unicode() {
+        assert_eq!(
+            read_str("greeting=\\u041f\\u0440\\u0438\\u0432\\u0435\\u0442\n"),
+            json(r#"{"greeting": "Привет"}"#)
+        );
+    }
+
+    #[test]
+    fn read_accepts_all_separators() {
+        assert_eq!(
+            read_str("a=1\nb:2\nc 3\nd = 4\ne\t:\t5\n"),
+            json(r#"{"a": "1", "b": "2", "c": "3", "d": "4", "e": "5"}"#)
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use std::io::{Read, Write};
+
+use crate::value::Value;
+
+/// Reads JSON document.
+pub fn read(input: impl Read) -> Result<Value, anyhow::Error> {
+    let value: Value = serde_json::from_reader(input)?;
+    Ok(value)
+}
+
+/// Writes document as JSON.
+pub fn write(value: &Value, output: impl Write) -> Result<(), anyhow::Error> {
+    serde_json::to_writer_pretty(output, value)?;
+    Ok(())
+}
+
+/// Writes documents as JSON array, one element at a time.
+pub fn write_documents(
+    documents: impl Iterator<Item = Result<Value, anyhow::Error>>,
+    mut output: impl Write,
+) -> Result<(), anyhow::Error> {
+    output.write_all(b"[")?;
+    let mut empty = true;
+    for (i, value) in documents.enumerate() {
+        let value = serde_json::to_string_pretty(&value?)?;
+        if i > 0 {
+            output.write_all(b",")?;
+        }
+        output.write_all(b"\n  ")?;
+        output.write_all(value.replace('\n', "\n  ").as_bytes())?;
+        empty = false;
+    }
+    output.write_all(if empty { b"]" } else { b"\n]" })?;
+    Ok(())
+}
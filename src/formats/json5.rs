@@ -0,0 +1,17 @@
+use std::io::{Read, Write};
+
+use crate::{formats, value::Value};
+
+/// Reads JSON5 document. Comments, trailing commas, unquoted keys and
+/// single-quoted strings are accepted, so JSONC is read as well.
+pub fn read(mut input: impl Read) -> Result<Value, anyhow::Error> {
+    let mut json5 = String::new();
+    input.read_to_string(&mut json5)?;
+    let value: Value = ::json5::from_str(&json5)?;
+    Ok(value)
+}
+
+/// Writes document as plain JSON, which is valid JSON5 and JSONC.
+pub fn write(value: &Value, output: impl Write) -> Result<(), anyhow::Error> {
+    formats::json::write(value, output)
+}
@@ -0,0 +1,59 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use anyhow::anyhow;
+
+use crate::value::Value;
+
+/// Reads JSON Lines lazily, one document per non-empty line.
+pub fn read_documents<'a>(
+    input: impl Read + 'a,
+) -> impl Iterator<Item = Result<Value, anyhow::Error>> + 'a {
+    BufReader::new(input)
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str(&line)
+                    .map_err(|x| anyhow!("Error at line {}: {}", number + 1, x)),
+            ),
+            Err(x) => Some(Err(x.into())),
+        })
+}
+
+/// Reads JSON Lines into array of documents.
+pub fn read(input: impl Read) -> Result<Value, anyhow::Error> {
+    let documents = read_documents(input).collect::<Result<_, _>>()?;
+    Ok(Value::Array(documents))
+}
+
+fn write_line(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    serde_json::to_writer(&mut output, value)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Writes documents one per line as they come.
+pub fn write_documents(
+    documents: impl Iterator<Item = Result<Value, anyhow::Error>>,
+    mut output: impl Write,
+) -> Result<(), anyhow::Error> {
+    for value in documents {
+        write_line(&value?, &mut output)?;
+    }
+    Ok(())
+}
+
+/// Writes document as JSON Lines: every element of array on its own line, or
+/// the whole document on a single line.
+pub fn write(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    match value {
+        Value::Array(x) => {
+            for value in x {
+                write_line(value, &mut output)?;
+            }
+            Ok(())
+        }
+        x => write_line(x, output),
+    }
+}
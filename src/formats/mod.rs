@@ -0,0 +1,26 @@
+//! Readers and writers of every supported format.
+
+use std::io::{self, Read, Write};
+
+pub mod bson;
+pub mod cbor;
+pub mod csv;
+pub mod dotenv;
+pub mod hcl;
+pub mod ini;
+pub mod java_properties;
+pub mod json;
+pub mod json5;
+pub mod jsonl;
+pub mod msgpack;
+pub mod protobuf;
+pub mod textproto;
+pub mod toml;
+pub mod xml;
+pub mod yaml;
+
+/// Copies input to output without any conversion.
+pub fn just_write(mut input: impl Read, mut output: impl Write) -> Result<(), anyhow::Error> {
+    io::copy(&mut input, &mut output)?;
+    Ok(())
+}
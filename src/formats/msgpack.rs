@@ -0,0 +1,101 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use base64::prelude::*;
+use indexmap::IndexMap;
+
+use crate::value::Value;
+
+const EXT_KEY: &str = "$ext";
+const VALUE_KEY: &str = "$value";
+
+fn from_msgpack(value: rmpv::Value) -> Result<Value, anyhow::Error> {
+    match value {
+        rmpv::Value::Nil => Ok(Value::Null),
+        rmpv::Value::Boolean(x) => Ok(Value::Bool(x)),
+        rmpv::Value::Integer(x) => match x.as_i64() {
+            Some(x) => Ok(Value::Integer(x as i128)),
+            None => Ok(Value::Integer(x.as_u64().unwrap() as i128)),
+        },
+        rmpv::Value::F32(x) => Ok(Value::Float(x as f64)),
+        rmpv::Value::F64(x) => Ok(Value::Float(x)),
+        rmpv::Value::String(x) if x.is_str() => Ok(Value::String(x.into_str().unwrap())),
+        rmpv::Value::String(x) => Ok(Value::Bytes(x.into_bytes())),
+        rmpv::Value::Binary(x) => Ok(Value::Bytes(x)),
+        rmpv::Value::Array(x) => Ok(Value::Array(
+            x.into_iter().map(from_msgpack).collect::<Result<_, _>>()?,
+        )),
+        rmpv::Value::Map(x) => {
+            let mut object = IndexMap::new();
+            for (k, v) in x {
+                let k = from_msgpack(k)?;
+                let Some(k) = k.to_scalar_string() else {
+                    return Err(anyhow!("{} cannot be used as a key", k.kind()));
+                };
+                object.insert(k, from_msgpack(v)?);
+            }
+            Ok(Value::Object(object))
+        }
+        rmpv::Value::Ext(kind, data) => Ok(Value::Object(IndexMap::from([
+            (EXT_KEY.to_string(), Value::Integer(kind as i128)),
+            (VALUE_KEY.to_string(), Value::Bytes(data)),
+        ]))),
+    }
+}
+
+fn to_msgpack(value: &Value) -> Result<rmpv::Value, anyhow::Error> {
+    match value {
+        Value::Null => Ok(rmpv::Value::Nil),
+        Value::Bool(x) => Ok(rmpv::Value::Boolean(*x)),
+        Value::Integer(x) => {
+            if let Ok(x) = i64::try_from(*x) {
+                Ok(rmpv::Value::from(x))
+            } else if let Ok(x) = u64::try_from(*x) {
+                Ok(rmpv::Value::from(x))
+            } else {
+                Err(anyhow!("Integer {} is out of range for MessagePack", x))
+            }
+        }
+        Value::Float(x) => Ok(rmpv::Value::F64(*x)),
+        Value::String(x) | Value::Datetime(x) => Ok(rmpv::Value::from(x.as_str())),
+        Value::Bytes(x) => Ok(rmpv::Value::Binary(x.clone())),
+        Value::Array(x) => Ok(rmpv::Value::Array(
+            x.iter().map(to_msgpack).collect::<Result<_, _>>()?,
+        )),
+        Value::Object(x) => {
+            if let (2, Some(Value::Integer(kind)), Some(data)) =
+                (x.len(), x.get(EXT_KEY), x.get(VALUE_KEY))
+            {
+                let kind = i8::try_from(*kind)
+                    .map_err(|_| anyhow!("Extension type {} is out of range", kind))?;
+                let data = match data {
+                    Value::Bytes(x) => x.clone(),
+                    Value::String(x) => BASE64_STANDARD.decode(x)?,
+                    x => return Err(anyhow!("Extension data cannot be {}", x.kind())),
+                };
+                return Ok(rmpv::Value::Ext(kind, data));
+            }
+            Ok(rmpv::Value::Map(
+                x.iter()
+                    .map(|(k, v)| Ok((rmpv::Value::from(k.as_str()), to_msgpack(v)?)))
+                    .collect::<Result<_, anyhow::Error>>()?,
+            ))
+        }
+    }
+}
+
+/// Reads MessagePack value.
+///
+/// Binary data is read as bytes, non-string map keys are converted to
+/// strings and extension types become objects with `$ext` type and `$value`
+/// data.
+pub fn read(mut input: impl Read) -> Result<Value, anyhow::Error> {
+    from_msgpack(rmpv::decode::read_value(&mut input)?)
+}
+
+/// Writes document as MessagePack value. Objects with `$ext` and `$value`
+/// keys are written as extension types, `$value` may be base64 string.
+pub fn write(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    rmpv::encode::write_value(&mut output, &to_msgpack(value)?)?;
+    Ok(())
+}
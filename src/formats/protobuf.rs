@@ -0,0 +1,851 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use protobuf::{
+    Message, MessageDyn,
+    descriptor::FileDescriptorSet,
+    reflect::{
+        FileDescriptor, MessageDescriptor, ReflectFieldRef, ReflectValueBox, ReflectValueRef,
+        RuntimeFieldType, RuntimeType,
+    },
+    well_known_types,
+};
+
+use crate::{options, value::Value};
+
+/// Finds message by fully-qualified name (`acme.billing.v1.Invoice`, leading
+/// dot is allowed) or by its trailing part (`Invoice`, `Order.Item`).
+fn find_message_descriptor(
+    files: &[FileDescriptor],
+    message_name: &str,
+) -> Result<MessageDescriptor, anyhow::Error> {
+    fn collect(message: MessageDescriptor, messages: &mut Vec<MessageDescriptor>) {
+        for nested in message.nested_messages() {
+            if !nested.is_map_entry() {
+                collect(nested, messages);
+            }
+        }
+        messages.push(message);
+    }
+
+    let mut messages = Vec::new();
+    for file in files {
+        for message in file.messages() {
+            collect(message, &mut messages);
+        }
+    }
+    if let Some(full_name) = message_name.strip_prefix('.') {
+        return messages
+            .into_iter()
+            .find(|x| x.full_name() == full_name)
+            .ok_or_else(|| anyhow!("Message {} is not found in input files", message_name));
+    }
+    if let Some(message) = messages.iter().find(|x| x.full_name() == message_name) {
+        return Ok(message.clone());
+    }
+    let suffix = format!(".{}", message_name);
+    let mut candidates: Vec<MessageDescriptor> = messages
+        .into_iter()
+        .filter(|x| x.full_name().ends_with(&suffix))
+        .collect();
+    candidates.sort_by(|a, b| a.full_name().cmp(b.full_name()));
+    candidates.dedup_by(|a, b| a.full_name() == b.full_name());
+    match candidates.len() {
+        0 => Err(anyhow!(
+            "Message {} is not found in input files",
+            message_name
+        )),
+        1 => Ok(candidates.pop().unwrap()),
+        _ => Err(anyhow!(
+            "Message name {} is ambiguous, use one of fully-qualified names: {}",
+            message_name,
+            candidates
+                .iter()
+                .map(|x| x.full_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn get_protobuf_message_descriptor(
+    input: Vec<String>,
+    include: Vec<String>,
+    descriptor_set: Vec<String>,
+    message_name: String,
+) -> Result<MessageDescriptor, anyhow::Error> {
+    if input.is_empty() && descriptor_set.is_empty() {
+        return Err(anyhow!(
+            "Either .proto files or descriptor set is required to find message {}",
+            message_name
+        ));
+    }
+    let mut file_descriptor_protos = Vec::new();
+    if !input.is_empty() {
+        let mut parser = protobuf_parse::Parser::new();
+        let parser = parser.pure().inputs(input).includes(include);
+        file_descriptor_protos.extend(parser.parse_and_typecheck()?.file_descriptors);
+    }
+    for path in descriptor_set {
+        let bytes = std::fs::read(&path)
+            .map_err(|x| anyhow!("Error while reading descriptor set {}: {}", path, x))?;
+        let set = FileDescriptorSet::parse_from_bytes(&bytes)
+            .map_err(|x| anyhow!("Error while parsing descriptor set {}: {}", path, x))?;
+        for file in set.file {
+            if !file_descriptor_protos
+                .iter()
+                .any(|x| x.name() == file.name())
+            {
+                file_descriptor_protos.push(file);
+            }
+        }
+    }
+    // Well-known types are resolved to the compiled ones, which JSON mapping
+    // recognizes to print timestamps, durations, wrappers and structs in
+    // their canonical form.
+    let well_known_types = well_known_types();
+    let (well_known_protos, file_descriptor_protos): (Vec<_>, Vec<_>) = file_descriptor_protos
+        .into_iter()
+        .partition(|x| well_known_types.iter().any(|y| y.name() == x.name()));
+    let mut files = FileDescriptor::new_dynamic_fds(file_descriptor_protos, &well_known_types)?;
+    files.extend(
+        well_known_types
+            .into_iter()
+            .filter(|x| well_known_protos.iter().any(|y| y.name() == x.name())),
+    );
+    find_message_descriptor(&files, &message_name)
+}
+
+/// Compiled `google/protobuf/*.proto` files that schemas may import.
+fn well_known_types() -> Vec<FileDescriptor> {
+    vec![
+        protobuf::descriptor::file_descriptor().clone(),
+        well_known_types::any::file_descriptor().clone(),
+        well_known_types::api::file_descriptor().clone(),
+        well_known_types::duration::file_descriptor().clone(),
+        well_known_types::empty::file_descriptor().clone(),
+        well_known_types::field_mask::file_descriptor().clone(),
+        well_known_types::source_context::file_descriptor().clone(),
+        well_known_types::struct_::file_descriptor().clone(),
+        well_known_types::timestamp::file_descriptor().clone(),
+        well_known_types::type_::file_descriptor().clone(),
+        well_known_types::wrappers::file_descriptor().clone(),
+    ]
+}
+
+fn convert_protobuf_value(
+    value: ReflectValueRef,
+    target: &RuntimeType,
+    path: &str,
+    dropped: &mut Vec<String>,
+) -> Option<ReflectValueBox> {
+    match (value, target) {
+        (ReflectValueRef::Message(x), RuntimeType::Message(descriptor)) => {
+            let mut message = descriptor.new_instance();
+            copy_protobuf_message(&*x, message.as_mut(), path, dropped);
+            Some(ReflectValueBox::Message(message))
+        }
+        (ReflectValueRef::Enum(source, number), RuntimeType::Enum(descriptor)) => {
+            let number = source
+                .value_by_number(number)
+                .and_then(|x| descriptor.value_by_name(x.name()))
+                .map(|x| x.value())
+                .unwrap_or(number);
+            Some(ReflectValueBox::Enum(descriptor.clone(), number))
+        }
+        (value, target) if value.get_type() == *target => Some(value.to_box()),
+        _ => None,
+    }
+}
+
+fn copy_protobuf_message(
+    source: &dyn MessageDyn,
+    target: &mut dyn MessageDyn,
+    path: &str,
+    dropped: &mut Vec<String>,
+) {
+    let field_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    // Fields unknown to the source schema are reported by number.
+    let mut unknown_numbers = Vec::new();
+    for (number, _) in source.special_fields_dyn().unknown_fields() {
+        if !unknown_numbers.contains(&number) {
+            unknown_numbers.push(number);
+            dropped.push(field_path(&number.to_string()));
+        }
+    }
+    let target_descriptor = target.descriptor_dyn();
+    for source_field in source.descriptor_dyn().fields() {
+        let field_path = field_path(source_field.name());
+        let is_set = match source_field.get_reflect(source) {
+            ReflectFieldRef::Optional(x) => x.value().is_some(),
+            ReflectFieldRef::Repeated(x) => !x.is_empty(),
+            ReflectFieldRef::Map(x) => !x.is_empty(),
+        };
+        if !is_set {
+            continue;
+        }
+        let target_field = target_descriptor
+            .field_by_name_or_json_name(source_field.name())
+            .or_else(|| target_descriptor.field_by_name_or_json_name(source_field.json_name()));
+        let Some(target_field) = target_field else {
+            dropped.push(field_path);
+            continue;
+        };
+        match (
+            source_field.get_reflect(source),
+            target_field.runtime_field_type(),
+        ) {
+            (ReflectFieldRef::Optional(x), RuntimeFieldType::Singular(t)) => {
+                match convert_protobuf_value(x.value().unwrap(), &t, &field_path, dropped) {
+                    Some(value) => target_field.set_singular_field(target, value),
+                    None => dropped.push(field_path),
+                }
+            }
+            (ReflectFieldRef::Repeated(x), RuntimeFieldType::Repeated(t)) => {
+                let mut values = Vec::new();
+                for value in x {
+                    match convert_protobuf_value(value, &t, &field_path, dropped) {
+                        Some(value) => values.push(value),
+                        None => {
+                            dropped.push(field_path.clone());
+                            break;
+                        }
+                    }
+                }
+                let mut repeated = target_field.mut_repeated(target);
+                for value in values {
+                    repeated.push(value);
+                }
+            }
+            (ReflectFieldRef::Map(x), RuntimeFieldType::Map(k, v)) => {
+                let mut entries = Vec::new();
+                for (key, value) in &x {
+                    let key = convert_protobuf_value(key, &k, &field_path, dropped);
+                    let value = convert_protobuf_value(value, &v, &field_path, dropped);
+                    match (key, value) {
+                        (Some(key), Some(value)) => entries.push((key, value)),
+                        _ => {
+                            dropped.push(field_path.clone());
+                            break;
+                        }
+                    }
+                }
+                let mut map = target_field.mut_map(target);
+                for (key, value) in entries {
+                    map.insert(key, value);
+                }
+            }
+            _ => dropped.push(field_path),
+        }
+    }
+}
+
+/// Copies message into a new message of the given type. Returns the new
+/// message and paths of fields that have no compatible counterpart, including
+/// fields unknown to the source schema.
+pub(crate) fn copy_to_message(
+    source: &dyn MessageDyn,
+    message_descriptor: &MessageDescriptor,
+) -> (Box<dyn MessageDyn>, Vec<String>) {
+    let mut target = message_descriptor.new_instance();
+    let mut dropped = Vec::new();
+    copy_protobuf_message(source, target.as_mut(), "", &mut dropped);
+    (target, dropped)
+}
+
+/// Shortens fraction of seconds to 0, 3 or 6 digits when the rest are zeros,
+/// as proto3 JSON mapping allows: `1.500000000s` becomes `1.500s`.
+fn trim_nanos(value: &str) -> String {
+    let Some((seconds, rest)) = value.split_once('.') else {
+        return value.to_string();
+    };
+    let digits = rest.chars().take_while(|x| x.is_ascii_digit()).count();
+    let (nanos, suffix) = rest.split_at(digits);
+    if digits != 9 {
+        return value.to_string();
+    }
+    let nanos = nanos.trim_end_matches('0');
+    let nanos = match nanos.len() {
+        0 => return format!("{}{}", seconds, suffix),
+        1..=3 => &rest[..3],
+        4..=6 => &rest[..6],
+        _ => &rest[..9],
+    };
+    format!("{}.{}{}", seconds, nanos, suffix)
+}
+
+/// Shortens timestamps and durations that JSON mapping prints with all nine
+/// digits of nanoseconds, looking for them by message schema.
+fn trim_well_known_types(value: &mut Value, message_descriptor: &MessageDescriptor) {
+    let full_name = message_descriptor.full_name();
+    if matches!(
+        full_name,
+        "google.protobuf.Timestamp" | "google.protobuf.Duration"
+    ) {
+        if let Value::String(x) = value {
+            *x = trim_nanos(x);
+        }
+        return;
+    }
+    // Other well-known types have their own JSON shape, like arbitrary objects
+    // of `Struct`, which must not be matched against fields.
+    if full_name.starts_with("google.protobuf.") {
+        return;
+    }
+    let Value::Object(object) = value else {
+        return;
+    };
+    for field in message_descriptor.fields() {
+        let Some(value) = object.get_mut(field.json_name()) else {
+            continue;
+        };
+        match field.runtime_field_type() {
+            RuntimeFieldType::Singular(RuntimeType::Message(x)) => {
+                trim_well_known_types(value, &x);
+            }
+            RuntimeFieldType::Repeated(RuntimeType::Message(x)) => {
+                if let Value::Array(values) = value {
+                    for value in values {
+                        trim_well_known_types(value, &x);
+                    }
+                }
+            }
+            RuntimeFieldType::Map(_, RuntimeType::Message(x)) => {
+                if let Value::Object(values) = value {
+                    for value in values.values_mut() {
+                        trim_well_known_types(value, &x);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn message_to_value(message: &dyn MessageDyn) -> Result<Value, anyhow::Error> {
+    let json = protobuf_json_mapping::print_to_string(message)?;
+    let mut value: Value = serde_json::from_str(json.as_str())?;
+    trim_well_known_types(&mut value, &message.descriptor_dyn());
+    Ok(value)
+}
+
+pub(crate) fn value_to_message(
+    message_descriptor: &MessageDescriptor,
+    value: &Value,
+) -> Result<Box<dyn MessageDyn>, anyhow::Error> {
+    let json = serde_json::to_string(value)?;
+    let message = protobuf_json_mapping::parse_dyn_from_str(message_descriptor, json.as_str())?;
+    Ok(message)
+}
+
+pub(crate) fn get_in_message_descriptor(
+    in_options: &options::protobuf::InOptions,
+) -> Result<MessageDescriptor, anyhow::Error> {
+    let Some(message_name) = in_options.get_message() else {
+        return Err(anyhow!(
+            "Message is required to be defined for protobuf deserialization"
+        ));
+    };
+    get_protobuf_message_descriptor(
+        in_options.get_input(),
+        in_options.get_include(),
+        in_options.get_descriptor_set(),
+        message_name,
+    )
+}
+
+pub(crate) fn get_out_message_descriptor(
+    out_options: &options::protobuf::OutOptions,
+) -> Result<MessageDescriptor, anyhow::Error> {
+    let Some(message_name) = out_options.get_message() else {
+        return Err(anyhow!(
+            "Message is required to be defined for protobuf serialization"
+        ));
+    };
+    get_protobuf_message_descriptor(
+        out_options.get_input(),
+        out_options.get_include(),
+        out_options.get_descriptor_set(),
+        message_name,
+    )
+}
+
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first()?;
+        *input = rest;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+/// Nesting of messages and groups that raw decoding follows, the same as
+/// `protoc --decode_raw` allows.
+const RAW_DEPTH_LIMIT: usize = 100;
+
+/// Decodes fields until the end of input or until end of group with given
+/// field number. Returns `None` if input is not a valid wire format or groups
+/// are nested deeper than the limit.
+fn decode_raw_fields(
+    input: &mut &[u8],
+    group: Option<u64>,
+    depth: usize,
+) -> Option<IndexMap<String, Value>> {
+    let mut fields: IndexMap<String, Value> = IndexMap::new();
+    while !input.is_empty() {
+        let tag = read_varint(input)?;
+        let number = tag >> 3;
+        if number == 0 || number > 0x1fffffff {
+            return None;
+        }
+        let (wire_type, value) = match tag & 7 {
+            0 => ("varint", Value::Integer(read_varint(input)? as i128)),
+            1 => {
+                let (bytes, rest) = input.split_first_chunk::<8>()?;
+                *input = rest;
+                (
+                    "fixed64",
+                    Value::Integer(u64::from_le_bytes(*bytes) as i128),
+                )
+            }
+            2 => {
+                let length = usize::try_from(read_varint(input)?).ok()?;
+                if length > input.len() {
+                    return None;
+                }
+                let (bytes, rest) = input.split_at(length);
+                *input = rest;
+                ("length_delimited", decode_raw_bytes(bytes, depth + 1))
+            }
+            3 if depth < RAW_DEPTH_LIMIT => (
+                "group",
+                Value::Object(decode_raw_fields(input, Some(number), depth + 1)?),
+            ),
+            4 if group == Some(number) => return Some(fields),
+            5 => {
+                let (bytes, rest) = input.split_first_chunk::<4>()?;
+                *input = rest;
+                (
+                    "fixed32",
+                    Value::Integer(u32::from_le_bytes(*bytes) as i128),
+                )
+            }
+            _ => return None,
+        };
+        let value = Value::Object(IndexMap::from([(wire_type.to_string(), value)]));
+        match fields.get_mut(&number.to_string()) {
+            Some(Value::Array(x)) => x.push(value),
+            Some(x) => {
+                let first = std::mem::replace(x, Value::Null);
+                *x = Value::Array(vec![first, value]);
+            }
+            None => {
+                fields.insert(number.to_string(), value);
+            }
+        }
+    }
+    group.is_none().then_some(fields)
+}
+
+/// Guesses what length-delimited field holds the way `protoc --decode_raw`
+/// does: a nested message, then printable text, then packed varints,
+/// otherwise bytes. Fields nested deeper than the limit are kept as bytes.
+fn decode_raw_bytes(bytes: &[u8], depth: usize) -> Value {
+    if bytes.is_empty() {
+        return Value::String(String::new());
+    }
+    if depth >= RAW_DEPTH_LIMIT {
+        return Value::Bytes(bytes.to_vec());
+    }
+    if let Some(x) = decode_raw_fields(&mut &bytes[..], None, depth) {
+        return Value::Object(x);
+    }
+    if let Ok(x) = std::str::from_utf8(bytes) {
+        if !x.contains(|c: char| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+            return Value::String(x.to_string());
+        }
+    }
+    let mut input = bytes;
+    let mut values = Vec::new();
+    while !input.is_empty() {
+        match read_varint(&mut input) {
+            Some(x) => values.push(Value::Integer(x as i128)),
+            None => return Value::Bytes(bytes.to_vec()),
+        }
+    }
+    Value::Array(values)
+}
+
+/// Decodes message without schema, like `protoc --decode_raw`. Fields are
+/// keyed by number, every occurrence is an object with wire type as the only
+/// key, and repeated occurrences are collected into arrays:
+/// `{"1": {"varint": 150}, "2": [{"length_delimited": "a"}, ...]}`.
+fn decode_raw(bytes: &[u8]) -> Result<Value, anyhow::Error> {
+    decode_raw_fields(&mut &bytes[..], None, 0)
+        .map(Value::Object)
+        .ok_or_else(|| anyhow!("Input is not a valid Protobuf wire format"))
+}
+
+/// Reads Protobuf binary message document.
+pub fn read(
+    mut input: impl Read,
+    in_options: &options::protobuf::InOptions,
+) -> Result<Value, anyhow::Error> {
+    if in_options.get_raw() {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        return decode_raw(&bytes);
+    }
+    let message_descriptor = get_in_message_descriptor(in_options)?;
+    let protobuf = message_descriptor.parse_from_reader(&mut input)?;
+    message_to_value(protobuf.as_ref())
+}
+
+/// Writes document as Protobuf binary message.
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::protobuf::OutOptions,
+) -> Result<(), anyhow::Error> {
+    let message_descriptor = get_out_message_descriptor(out_options)?;
+    let message = value_to_message(&message_descriptor, value)?;
+    message.write_to_writer_dyn(&mut output)?;
+    Ok(())
+}
+
+/// Reads stream of messages, each prefixed with its length as varint.
+/// Messages are read one at a time.
+pub fn read_delimited<'a>(
+    mut input: impl Read + 'a,
+    in_options: &options::protobuf::InOptions,
+) -> Result<impl Iterator<Item = Result<Value, anyhow::Error>> + 'a, anyhow::Error> {
+    fn read_length(input: &mut impl Read) -> Result<Option<u64>, anyhow::Error> {
+        let mut length = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0u8];
+            if input.read(&mut byte)? == 0 {
+                if shift == 0 {
+                    return Ok(None);
+                }
+                return Err(anyhow!("Unexpected end of input in message length"));
+            }
+            length |= ((byte[0] & 0x7f) as u64) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(length));
+            }
+        }
+        Err(anyhow!("Message length is too large"))
+    }
+
+    let message_descriptor = if in_options.get_raw() {
+        None
+    } else {
+        Some(get_in_message_descriptor(in_options)?)
+    };
+    let mut buf = Vec::new();
+    Ok(std::iter::from_fn(move || {
+        let length = match read_length(&mut input) {
+            Ok(Some(x)) => x,
+            Ok(None) => return None,
+            Err(x) => return Some(Err(x)),
+        };
+        buf.clear();
+        let result = (&mut input)
+            .take(length)
+            .read_to_end(&mut buf)
+            .map_err(anyhow::Error::from)
+            .and_then(|x| {
+                if (x as u64) < length {
+                    return Err(anyhow!("Unexpected end of input in message"));
+                }
+                let Some(message_descriptor) = &message_descriptor else {
+                    return decode_raw(&buf);
+                };
+                let message = message_descriptor.parse_from_bytes(&buf)?;
+                message_to_value(message.as_ref())
+            });
+        Some(result)
+    }))
+}
+
+/// Writes documents as stream of messages, each prefixed with its length as
+/// varint.
+pub fn write_delimited(
+    documents: impl Iterator<Item = Result<Value, anyhow::Error>>,
+    mut output: impl Write,
+    out_options: &options::protobuf::OutOptions,
+) -> Result<(), anyhow::Error> {
+    let message_descriptor = get_out_message_descriptor(out_options)?;
+    for value in documents {
+        let message = value_to_message(&message_descriptor, &value?)?;
+        message.write_length_delimited_to_writer_dyn(&mut output)?;
+    }
+    Ok(())
+}
+
+/// Re-encodes message from input schema to output schema, matching fields by
+/// name or JSON name. Uses input schema if output message is not defined.
+/// Returns paths of dropped fields, including fields unknown to input schema.
+pub fn transcode(
+    mut input: impl Read,
+    mut output: impl Write,
+    in_options: &options::protobuf::InOptions,
+    out_options: &options::protobuf::OutOptions,
+) -> Result<Vec<String>, anyhow::Error> {
+    if in_options.get_raw() {
+        return Err(anyhow!(
+            "Raw decoding cannot be used for Protobuf to Protobuf conversion"
+        ));
+    }
+    let in_message_descriptor = get_in_message_descriptor(in_options)?;
+    let out_message_descriptor = if out_options.get_message().is_some() {
+        get_out_message_descriptor(out_options)?
+    } else {
+        in_message_descriptor.clone()
+    };
+    let source = in_message_descriptor.parse_from_reader(&mut input)?;
+    let (target, dropped) = copy_to_message(source.as_ref(), &out_message_descriptor);
+    target.write_to_writer_dyn(&mut output)?;
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::MessageFull;
+
+    use super::*;
+    use crate::value::json;
+
+    fn files() -> Vec<FileDescriptor> {
+        let billing = r#"
+            name: "billing.proto"
+            package: "acme.billing.v1"
+            message_type { name: "Invoice" }
+            message_type { name: "Order" nested_type { name: "Item" } }
+        "#;
+        let shipping = r#"
+            name: "shipping.proto"
+            package: "acme.shipping.v1"
+            message_type { name: "Parcel" }
+            message_type { name: "Order" nested_type { name: "Item" } }
+        "#;
+        let protos = [billing, shipping]
+            .into_iter()
+            .map(|x| protobuf::text_format::parse_from_str(x).unwrap())
+            .collect();
+        FileDescriptor::new_dynamic_fds(protos, &[]).unwrap()
+    }
+
+    fn find(message_name: &str) -> Result<String, String> {
+        find_message_descriptor(&files(), message_name)
+            .map(|x| x.full_name().to_string())
+            .map_err(|x| x.to_string())
+    }
+
+    #[test]
+    fn find_message_descriptor_by_full_name() {
+        assert_eq!(
+            find("acme.billing.v1.Invoice"),
+            Ok("acme.billing.v1.Invoice".to_string())
+        );
+        assert_eq!(
+            find("acme.shipping.v1.Order.Item"),
+            Ok("acme.shipping.v1.Order.Item".to_string())
+        );
+    }
+
+    #[test]
+    fn find_message_descriptor_with_leading_dot() {
+        assert_eq!(
+            find(".acme.billing.v1.Invoice"),
+            Ok("acme.billing.v1.Invoice".to_string())
+        );
+        assert_eq!(
+            find(".Invoice"),
+            Err("Message .Invoice is not found in input files".to_string())
+        );
+    }
+
+    #[test]
+    fn find_message_descriptor_by_suffix() {
+        assert_eq!(find("Invoice"), Ok("acme.billing.v1.Invoice".to_string()));
+        assert_eq!(
+            find("billing.v1.Order.Item"),
+            Ok("acme.billing.v1.Order.Item".to_string())
+        );
+        assert_eq!(
+            find("Voice"),
+            Err("Message Voice is not found in input files".to_string())
+        );
+    }
+
+    #[test]
+    fn find_message_descriptor_reports_ambiguous_names() {
+        assert_eq!(
+            find("Order.Item"),
+            Err(
+                "Message name Order.Item is ambiguous, use one of fully-qualified names: \
+                acme.billing.v1.Order.Item, acme.shipping.v1.Order.Item"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn copy_to_message_reports_dropped_fields() {
+        let duration = well_known_types::duration::Duration::descriptor();
+        let source = duration
+            .parse_from_bytes(b"\x08\x01\x18\x05\x18\x06")
+            .unwrap();
+        let (target, dropped) = copy_to_message(source.as_ref(), &duration);
+        assert_eq!(dropped, vec!["3"]);
+        assert_eq!(target.write_to_bytes_dyn().unwrap(), b"\x08\x01");
+
+        let mut source = well_known_types::field_mask::FieldMask::new();
+        source.paths.push("a".to_string());
+        let (_, dropped) = copy_to_message(&source, &duration);
+        assert_eq!(dropped, vec!["paths"]);
+    }
+
+    #[test]
+    fn trim_nanos_keeps_groups_of_three_digits() {
+        assert_eq!(trim_nanos("1.500000000s"), "1.500s");
+        assert_eq!(trim_nanos("-1.000001000s"), "-1.000001s");
+        assert_eq!(trim_nanos("2.000000000s"), "2s");
+        assert_eq!(trim_nanos("0.123456789s"), "0.123456789s");
+        assert_eq!(trim_nanos("0.100000001s"), "0.100000001s");
+        assert_eq!(
+            trim_nanos("2024-01-02T03:04:05.500000000Z"),
+            "2024-01-02T03:04:05.500Z"
+        );
+        assert_eq!(
+            trim_nanos("2024-01-02T03:04:05.000000000Z"),
+            "2024-01-02T03:04:05Z"
+        );
+        assert_eq!(trim_nanos("1s"), "1s");
+    }
+
+    #[test]
+    fn decode_raw_reads_scalars() {
+        let bytes = b"\x08\x96\x01\x11\x01\0\0\0\0\0\0\0\x1d\x02\0\0\0";
+        assert_eq!(
+            decode_raw(bytes).unwrap(),
+            json(r#"{"1": {"varint": 150}, "2": {"fixed64": 1}, "3": {"fixed32": 2}}"#)
+        );
+    }
+
+    #[test]
+    fn decode_raw_collects_repeated_fields() {
+        assert_eq!(
+            decode_raw(b"\x08\x01\x08\x02\x08\x03").unwrap(),
+            json(r#"{"1": [{"varint": 1}, {"varint": 2}, {"varint": 3}]}"#)
+        );
+    }
+
+    #[test]
+    fn decode_raw_prefers_nested_message_over_string() {
+        let text = "The quick brown fox jumps over the lazy dog..";
+        let mut inner = vec![0x0a, text.len() as u8];
+        inner.extend(text.as_bytes());
+        let mut outer = vec![0x0a, inner.len() as u8];
+        outer.extend(&inner);
+        assert_eq!(
+            decode_raw(&outer).unwrap(),
+            json(&format!(
+                r#"{{"1": {{"length_delimited": {{"1": {{"length_delimited": "{}"}}}}}}}}"#,
+                text
+            ))
+        );
+    }
+
+    #[test]
+    fn decode_raw_guesses_length_delimited_values() {
+        assert_eq!(
+            decode_raw(b"\x0a\x05hello\x12\x00").unwrap(),
+            json(r#"{"1": {"length_delimited": "hello"}, "2": {"length_delimited": ""}}"#)
+        );
+        assert_eq!(
+            decode_raw(b"\x0a\x04\x01\x02\x96\x01").unwrap(),
+            json(r#"{"1": {"length_delimited": [1, 2, 150]}}"#)
+        );
+        assert_eq!(
+            decode_raw(b"\x0a\x02\xff\xfe").unwrap(),
+            Value::Object(IndexMap::from([(
+                "1".to_string(),
+                Value::Object(IndexMap::from([(
+                    "length_delimited".to_string(),
+                    Value::Bytes(vec![0xff, 0xfe])
+                )]))
+            )]))
+        );
+    }
+
+    #[test]
+    fn decode_raw_reads_groups() {
+        assert_eq!(
+            decode_raw(b"\x0b\x08\x01\x0c").unwrap(),
+            json(r#"{"1": {"group": {"1": {"varint": 1}}}}"#)
+        );
+        assert!(decode_raw(b"\x0b\x08\x01").is_err());
+        assert!(decode_raw(b"\x0c").is_err());
+    }
+
+    #[test]
+    fn decode_raw_stops_at_depth_limit() {
+        fn varint(mut x: usize, output: &mut Vec<u8>) {
+            while x >= 0x80 {
+                output.push((x as u8) | 0x80);
+                x >>= 7;
+            }
+            output.push(x as u8);
+        }
+
+        let inner = b"\x08\x01";
+        let mut headers = Vec::new();
+        let mut length = inner.len();
+        for _ in 0..100_000 {
+            let mut header = vec![0x0a];
+            varint(length, &mut header);
+            length += header.len();
+            headers.push(header);
+        }
+        let mut bytes: Vec<u8> = headers.into_iter().rev().flatten().collect();
+        bytes.extend(inner);
+
+        let mut value = decode_raw(&bytes).unwrap();
+        let mut depth = 0;
+        while let Value::Object(mut x) = value {
+            let Some(Value::Object(mut field)) = x.shift_remove("1") else {
+                panic!("Expected field 1 at depth {}", depth);
+            };
+            value = field.shift_remove("length_delimited").unwrap();
+            depth += 1;
+        }
+        assert_eq!(depth, RAW_DEPTH_LIMIT);
+        assert!(matches!(value, Value::Bytes(_)));
+
+        let mut groups = b"\x0b".repeat(RAW_DEPTH_LIMIT + 1);
+        groups.extend(b"\x0c".repeat(RAW_DEPTH_LIMIT + 1));
+        assert!(decode_raw(&groups).is_err());
+    }
+
+    #[test]
+    fn decode_raw_rejects_invalid_input() {
+        assert!(decode_raw(b"\x08").is_err());
+        assert!(decode_raw(b"\x08\x96").is_err());
+        assert!(decode_raw(b"\x0a\x05abc").is_err());
+        assert!(decode_raw(b"\x00\x01").is_err());
+        assert!(decode_raw(b"\x0f").is_err());
+    }
+}
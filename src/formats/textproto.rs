@@ -0,0 +1,58 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+
+use crate::{formats::protobuf, options, value::Value};
+
+/// Reads Protobuf text format message, using the same schema options as
+/// binary Protobuf.
+pub fn read(
+    mut input: impl Read,
+    in_options: &options::protobuf::InOptions,
+) -> Result<Value, anyhow::Error> {
+    if in_options.get_raw() {
+        return Err(anyhow!(
+            "Raw decoding is supported only for binary Protobuf"
+        ));
+    }
+    let message_descriptor = protobuf::get_in_message_descriptor(in_options)?;
+    let mut textproto = String::new();
+    input.read_to_string(&mut textproto)?;
+    let mut message = message_descriptor.new_instance();
+    ::protobuf::text_format::merge_from_str(message.as_mut(), &textproto)?;
+    protobuf::message_to_value(message.as_ref())
+}
+
+/// Writes document as Protobuf text format message.
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::protobuf::OutOptions,
+) -> Result<(), anyhow::Error> {
+    let message_descriptor = protobuf::get_out_message_descriptor(out_options)?;
+    let message = protobuf::value_to_message(&message_descriptor, value)?;
+    output
+        .write_all(::protobuf::text_format::print_to_string_pretty(message.as_ref()).as_bytes())?;
+    Ok(())
+}
+
+/// Re-encodes text format message from input schema to output schema, like
+/// [`protobuf::transcode`] does for binary messages. Returns paths of dropped
+/// fields.
+pub fn transcode(
+    mut input: impl Read,
+    mut output: impl Write,
+    in_options: &options::protobuf::InOptions,
+    out_options: &options::protobuf::OutOptions,
+) -> Result<Vec<String>, anyhow::Error> {
+    let in_message_descriptor = protobuf::get_in_message_descriptor(in_options)?;
+    let out_message_descriptor = protobuf::get_out_message_descriptor(out_options)?;
+    let mut textproto = String::new();
+    input.read_to_string(&mut textproto)?;
+    let mut source = in_message_descriptor.new_instance();
+    ::protobuf::text_format::merge_from_str(source.as_mut(), &textproto)?;
+    let (target, dropped) = protobuf::copy_to_message(source.as_ref(), &out_message_descriptor);
+    output
+        .write_all(::protobuf::text_format::print_to_string_pretty(target.as_ref()).as_bytes())?;
+    Ok(dropped)
+}
@@ -0,0 +1,62 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+
+use crate::value::Value;
+
+fn from_toml(toml: ::toml::Value) -> Value {
+    match toml {
+        ::toml::Value::String(x) => Value::String(x),
+        ::toml::Value::Integer(x) => Value::Integer(x as i128),
+        ::toml::Value::Float(x) => Value::Float(x),
+        ::toml::Value::Boolean(x) => Value::Bool(x),
+        ::toml::Value::Datetime(x) => Value::Datetime(x.to_string()),
+        ::toml::Value::Array(x) => Value::Array(x.into_iter().map(from_toml).collect()),
+        ::toml::Value::Table(x) => {
+            Value::Object(x.into_iter().map(|(k, v)| (k, from_toml(v))).collect())
+        }
+    }
+}
+
+fn to_toml(value: &Value) -> Result<::toml::Value, anyhow::Error> {
+    match value {
+        Value::Null => Err(anyhow!("TOML does not support null values in arrays")),
+        Value::Bool(x) => Ok(::toml::Value::Boolean(*x)),
+        Value::Integer(x) => i64::try_from(*x)
+            .map(::toml::Value::Integer)
+            .map_err(|_| anyhow!("Integer {} is out of range for TOML", x)),
+        Value::Float(x) => Ok(::toml::Value::Float(*x)),
+        Value::String(x) => Ok(::toml::Value::String(x.to_string())),
+        Value::Datetime(x) => Ok(::toml::Value::Datetime(x.parse()?)),
+        Value::Bytes(_) => Ok(::toml::Value::String(value.to_scalar_string().unwrap())),
+        Value::Array(x) => Ok(::toml::Value::Array(
+            x.iter().map(to_toml).collect::<Result<_, _>>()?,
+        )),
+        Value::Object(x) => {
+            let mut table = ::toml::Table::new();
+            for (k, v) in x {
+                if let Value::Null = v {
+                    continue;
+                }
+                table.insert(k.to_string(), to_toml(v)?);
+            }
+            Ok(::toml::Value::Table(table))
+        }
+    }
+}
+
+/// Reads TOML document.
+pub fn read(mut input: impl Read) -> Result<Value, anyhow::Error> {
+    let mut toml = String::new();
+    input.read_to_string(&mut toml)?;
+    let toml: ::toml::Value = ::toml::from_str(toml.as_str())?;
+    Ok(from_toml(toml))
+}
+
+/// Writes document as TOML.
+pub fn write(value: &Value, mut output: impl Write) -> Result<(), anyhow::Error> {
+    let toml = to_toml(value)?;
+    let toml = ::toml::to_string_pretty(&toml)?;
+    output.write_all(toml.as_bytes())?;
+    Ok(())
+}
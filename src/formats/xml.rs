@@ -0,0 +1,293 @@
+use std::io::{BufReader, Read, Write};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use quick_xml::{
+    Reader, Writer,
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+};
+
+use crate::{options, value::Value};
+
+const ATTRIBUTE_PREFIX: &str = "@";
+const TEXT_KEY: &str = "#text";
+const ROOT: &str = "root";
+const ARRAY_ITEM: &str = "item";
+
+struct Element {
+    name: String,
+    children: IndexMap<String, Value>,
+    text: String,
+}
+
+impl Element {
+    fn into_value(self, text_key: &str) -> Value {
+        let text = self.text.trim();
+        if self.children.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text.to_string())
+            };
+        }
+        let mut children = self.children;
+        if !text.is_empty() {
+            children.insert(text_key.to_string(), Value::String(text.to_string()));
+        }
+        Value::Object(children)
+    }
+
+    fn insert(&mut self, name: String, value: Value, force_array: bool) {
+        match self.children.get_mut(&name) {
+            Some(Value::Array(x)) => x.push(value),
+            Some(x) => {
+                let first = std::mem::replace(x, Value::Null);
+                *x = Value::Array(vec![first, value]);
+            }
+            None if force_array => {
+                self.children.insert(name, Value::Array(vec![value]));
+            }
+            None => {
+                self.children.insert(name, value);
+            }
+        }
+    }
+}
+
+/// Reads XML document into object with the single key named after the root
+/// element.
+///
+/// Attributes become keys prefixed with `@`, text of elements having
+/// attributes or children is stored under `#text`, and elements without them
+/// become plain strings. Repeated elements are collected into arrays.
+/// Namespace prefixes are kept in names unless stripped by options.
+pub fn read(
+    input: impl Read,
+    in_options: &options::xml::InOptions,
+) -> Result<Value, anyhow::Error> {
+    let attribute_prefix = in_options
+        .get_attribute_prefix()
+        .unwrap_or(ATTRIBUTE_PREFIX);
+    let text_key = in_options.get_text_key().unwrap_or(TEXT_KEY);
+    let array = in_options.get_array();
+    let strip_namespaces = in_options.get_strip_namespaces();
+    let get_name = |name: &[u8]| -> Result<String, anyhow::Error> {
+        let name = std::str::from_utf8(name)?;
+        if strip_namespaces {
+            Ok(name.rsplit(':').next().unwrap().to_string())
+        } else {
+            Ok(name.to_string())
+        }
+    };
+    let start = |x: &BytesStart| -> Result<Element, anyhow::Error> {
+        let mut children = IndexMap::new();
+        for attribute in x.attributes() {
+            let attribute = attribute?;
+            let key = attribute.key.as_ref();
+            if strip_namespaces && (key == b"xmlns" || key.starts_with(b"xmlns:")) {
+                continue;
+            }
+            children.insert(
+                format!("{}{}", attribute_prefix, get_name(key)?),
+                Value::String(attribute.unescape_value()?.into_owned()),
+            );
+        }
+        Ok(Element {
+            name: get_name(x.name().as_ref())?,
+            children,
+            text: String::new(),
+        })
+    };
+
+    let mut reader = Reader::from_reader(BufReader::new(input));
+    let mut buf = Vec::new();
+    let mut stack: Vec<Element> = vec![Element {
+        name: String::new(),
+        children: IndexMap::new(),
+        text: String::new(),
+    }];
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(x) => stack.push(start(&x)?),
+            Event::Empty(x) => {
+                let element = start(&x)?;
+                let force_array = array.contains(&element.name);
+                let name = element.name.clone();
+                let value = element.into_value(text_key);
+                stack.last_mut().unwrap().insert(name, value, force_array);
+            }
+            Event::End(_) => {
+                let element = stack.pop().unwrap();
+                let force_array = array.contains(&element.name);
+                let name = element.name.clone();
+                let value = element.into_value(text_key);
+                let Some(parent) = stack.last_mut() else {
+                    return Err(anyhow!("Unexpected closing tag {}", name));
+                };
+                parent.insert(name, value, force_array);
+            }
+            Event::Text(x) => stack.last_mut().unwrap().text.push_str(&x.unescape()?),
+            Event::CData(x) => stack
+                .last_mut()
+                .unwrap()
+                .text
+                .push_str(std::str::from_utf8(&x)?),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    if stack.len() != 1 {
+        return Err(anyhow!("Unexpected end of XML document"));
+    }
+    let document = stack.pop().unwrap();
+    if document.children.is_empty() {
+        return Err(anyhow!("XML document has no root element"));
+    }
+    Ok(Value::Object(document.children))
+}
+
+/// Checks name against the `Name` production of XML specification.
+fn is_xml_name(name: &str) -> bool {
+    fn is_name_start_char(c: char) -> bool {
+        matches!(
+            c,
+            ':' | 'A'..='Z'
+                | '_'
+                | 'a'..='z'
+                | '\u{C0}'..='\u{D6}'
+                | '\u{D8}'..='\u{F6}'
+                | '\u{F8}'..='\u{2FF}'
+                | '\u{370}'..='\u{37D}'
+                | '\u{37F}'..='\u{1FFF}'
+                | '\u{200C}'..='\u{200D}'
+                | '\u{2070}'..='\u{218F}'
+                | '\u{2C00}'..='\u{2FEF}'
+                | '\u{3001}'..='\u{D7FF}'
+                | '\u{F900}'..='\u{FDCF}'
+                | '\u{FDF0}'..='\u{FFFD}'
+                | '\u{10000}'..='\u{EFFFF}'
+        )
+    }
+
+    fn is_name_char(c: char) -> bool {
+        is_name_start_char(c)
+            || matches!(
+                c,
+                '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}'
+            )
+    }
+
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start_char) && chars.all(is_name_char)
+}
+
+/// Writes document as XML. Object with the single key is written as the root
+/// element, anything else is wrapped into root element named by options.
+pub fn write(
+    value: &Value,
+    mut output: impl Write,
+    out_options: &options::xml::OutOptions,
+) -> Result<(), anyhow::Error> {
+    fn write_element<W: Write>(
+        writer: &mut Writer<W>,
+        name: &str,
+        value: &Value,
+        attribute_prefix: &str,
+        text_key: &str,
+    ) -> Result<(), anyhow::Error> {
+        if !is_xml_name(name) {
+            return Err(anyhow!("Key {} is not a valid XML element name", name));
+        }
+        match value {
+            Value::Null => {
+                writer.write_event(Event::Empty(BytesStart::new(name)))?;
+            }
+            Value::Array(x) => {
+                for value in x {
+                    if let Value::Array(_) = value {
+                        return Err(anyhow!(
+                            "Element {} cannot contain directly nested arrays",
+                            name
+                        ));
+                    }
+                    write_element(writer, name, value, attribute_prefix, text_key)?;
+                }
+            }
+            Value::Object(x) => {
+                let mut start = BytesStart::new(name);
+                let mut text = None;
+                let mut children = Vec::new();
+                for (k, v) in x {
+                    if k == text_key {
+                        text = v.to_scalar_string();
+                    } else if let Some(attribute) = k.strip_prefix(attribute_prefix) {
+                        let Some(v) = v.to_scalar_string() else {
+                            return Err(anyhow!(
+                                "Attribute {} of element {} cannot be {}",
+                                attribute,
+                                name,
+                                v.kind()
+                            ));
+                        };
+                        if !is_xml_name(attribute) {
+                            return Err(anyhow!("Key {} is not a valid XML attribute name", k));
+                        }
+                        start.push_attribute((attribute, v.as_str()));
+                    } else {
+                        children.push((k, v));
+                    }
+                }
+                if text.is_none() && children.is_empty() {
+                    writer.write_event(Event::Empty(start))?;
+                    return Ok(());
+                }
+                writer.write_event(Event::Start(start))?;
+                if let Some(text) = text {
+                    writer.write_event(Event::Text(BytesText::new(&text)))?;
+                }
+                for (k, v) in children {
+                    write_element(writer, k, v, attribute_prefix, text_key)?;
+                }
+                writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            x => {
+                let text = x.to_scalar_string().unwrap();
+                writer.write_event(Event::Start(BytesStart::new(name)))?;
+                writer.write_event(Event::Text(BytesText::new(&text)))?;
+                writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+        }
+        Ok(())
+    }
+
+    let attribute_prefix = out_options
+        .get_attribute_prefix()
+        .unwrap_or(ATTRIBUTE_PREFIX);
+    let text_key = out_options.get_text_key().unwrap_or(TEXT_KEY);
+    // Document is buffered so that nothing is written if some key is not a
+    // valid name.
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    match value {
+        Value::Object(x) if x.len() == 1 && !matches!(x[0], Value::Array(_)) => {
+            let (name, value) = x.first().unwrap();
+            write_element(&mut writer, name, value, attribute_prefix, text_key)?;
+        }
+        Value::Array(x) => {
+            let root = Value::Object(IndexMap::from([(
+                ARRAY_ITEM.to_string(),
+                Value::Array(x.clone()),
+            )]));
+            let name = out_options.get_root().unwrap_or(ROOT);
+            write_element(&mut writer, name, &root, attribute_prefix, text_key)?;
+        }
+        x => {
+            let name = out_options.get_root().unwrap_or(ROOT);
+            write_element(&mut writer, name, x, attribute_prefix, text_key)?;
+        }
+    }
+    writer.get_mut().write_all(b"\n")?;
+    output.write_all(&writer.into_inner())?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use serde::Deserialize;
+
+use crate::{options, value::Value};
+
+/// Reads YAML document. Stream of several `---` separated documents is read
+/// as array of them, unless a single document is selected by options.
+pub fn read(
+    input: impl Read,
+    in_options: &options::yaml::InOptions,
+) -> Result<Value, anyhow::Error> {
+    let mut documents = read_documents(input, in_options)?;
+    match documents.len() {
+        0 => Ok(Value::Null),
+        1 => Ok(documents.pop().unwrap()),
+        _ => Ok(Value::Array(documents)),
+    }
+}
+
+/// Writes document as YAML.
+pub fn write(value: &Value, output: impl Write) -> Result<(), anyhow::Error> {
+    serde_yaml::to_writer(output, value)?;
+    Ok(())
+}
+
+/// Reads all `---` separated documents of YAML stream, or only the one
+/// selected by options. Empty documents, like the one after trailing `---` or
+/// holding only comments, are skipped and not counted by the selected index.
+pub fn read_documents(
+    input: impl Read,
+    in_options: &options::yaml::InOptions,
+) -> Result<Vec<Value>, anyhow::Error> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_reader(input) {
+        let document = Value::deserialize(document)?;
+        if document != Value::Null {
+            documents.push(document);
+        }
+    }
+    if let Some(index) = in_options.get_document() {
+        if index >= documents.len() {
+            return Err(anyhow!(
+                "Document {} is requested but YAML stream has {} documents",
+                index,
+                documents.len()
+            ));
+        }
+        return Ok(vec![documents.swap_remove(index)]);
+    }
+    Ok(documents)
+}
+
+/// Writes documents as YAML stream separated by `---`.
+pub fn write_documents(
+    documents: impl Iterator<Item = Result<Value, anyhow::Error>>,
+    mut output: impl Write,
+) -> Result<(), anyhow::Error> {
+    for (i, value) in documents.enumerate() {
+        let value = value?;
+        if i > 0 {
+            output.write_all(b"---\n")?;
+        }
+        serde_yaml::to_writer(&mut output, &value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{options::Options, value::json};
+
+    const STREAM: &str = "# manifests\n---\na: 1\n---\n# only a comment\n---\nb: 2\n---\n";
+
+    #[test]
+    fn read_documents_skips_empty_documents() {
+        let documents =
+            read_documents(STREAM.as_bytes(), &options::yaml::InOptions::new()).unwrap();
+        assert_eq!(documents, vec![json(r#"{"a": 1}"#), json(r#"{"b": 2}"#)]);
+    }
+
+    #[test]
+    fn read_documents_selects_index_among_non_empty_documents() {
+        let mut in_options = options::yaml::InOptions::new();
+        in_options.set(options::yaml::InOption::Document(1));
+        assert_eq!(
+            read_documents(STREAM.as_bytes(), &in_options).unwrap(),
+            vec![json(r#"{"b": 2}"#)]
+        );
+        in_options.set(options::yaml::InOption::Document(2));
+        assert!(read_documents(STREAM.as_bytes(), &in_options).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Conversion of data between structured formats.
+//!
+//! Every format has a reader producing a [`Value`] and a writer consuming it,
+//! see [`formats`]. [`convert`] wires them together:
+//!
+//! ```
+//! use cvto::{Format, convert, options::ConvertOptions};
+//!
+//! let input = r#"{"server": {"port": 8080}}"#;
+//! let mut output = Vec::new();
+//! convert(
+//!     input.as_bytes(),
+//!     Format::Json,
+//!     &mut output,
+//!     Format::JavaProperties,
+//!     &ConvertOptions::default(),
+//! )
+//! .unwrap();
+//! assert_eq!(String::from_utf8(output).unwrap(), "server.port=8080\n");
+//! ```
+
+use std::{
+    fmt,
+    io::{Read, Write},
+    path::Path,
+};
+
+pub mod detect;
+pub mod formats;
+pub mod options;
+pub mod value;
+
+pub use value::Value;
+
+/// Supported data format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+    JavaProperties,
+    Protobuf,
+    Csv,
+    Tsv,
+    Xml,
+    Ini,
+    Dotenv,
+    Hcl,
+    Json5,
+    Jsonc,
+    Jsonl,
+    Msgpack,
+    Cbor,
+    Bson,
+    Textproto,
+}
+
+impl Format {
+    /// Determines format by the extension of the file. Files named `.env` or
+    /// `.env.*` are dotenv unless the suffix is a known extension, like in
+    /// `.env.json`.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let file_name = path.file_name()?.to_string_lossy();
+        let format = path
+            .extension()
+            .and_then(|x| Self::from_extension(&x.to_string_lossy()));
+        if format.is_none() && (file_name == ".env" || file_name.starts_with(".env.")) {
+            return Some(Format::Dotenv);
+        }
+        format
+    }
+
+    fn from_extension(extension: &str) -> Option<Format> {
+        match extension {
+            "json" => Some(Format::Json),
+            "yaml" => Some(Format::Yaml),
+            "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            "properties" => Some(Format::JavaProperties),
+            "protobuf" => Some(Format::Protobuf),
+            "csv" => Some(Format::Csv),
+            "tsv" => Some(Format::Tsv),
+            "xml" => Some(Format::Xml),
+            "ini" => Some(Format::Ini),
+            "cfg" => Some(Format::Ini),
+            "env" => Some(Format::Dotenv),
+            "hcl" => Some(Format::Hcl),
+            "tf" => Some(Format::Hcl),
+            "json5" => Some(Format::Json5),
+            "jsonc" => Some(Format::Jsonc),
+            "jsonl" => Some(Format::Jsonl),
+            "ndjson" => Some(Format::Jsonl),
+            "msgpack" => Some(Format::Msgpack),
+            "mpk" => Some(Format::Msgpack),
+            "cbor" => Some(Format::Cbor),
+            "bson" => Some(Format::Bson),
+            "textproto" => Some(Format::Textproto),
+            "pbtxt" => Some(Format::Textproto),
+            "txtpb" => Some(Format::Textproto),
+            _ => None,
+        }
+    }
+
+    /// Name of the format, as accepted by `-i` and `-o` of the CLI.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::JavaProperties => "properties",
+            Format::Protobuf => "protobuf",
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+            Format::Xml => "xml",
+            Format::Ini => "ini",
+            Format::Dotenv => "dotenv",
+            Format::Hcl => "hcl",
+            Format::Json5 => "json5",
+            Format::Jsonc => "jsonc",
+            Format::Jsonl => "jsonl",
+            Format::Msgpack => "msgpack",
+            Format::Cbor => "cbor",
+            Format::Bson => "bson",
+            Format::Textproto => "textproto",
+        }
+    }
+
+    /// Extension of files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::JavaProperties => "properties",
+            Format::Protobuf => "protobuf",
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+            Format::Xml => "xml",
+            Format::Ini => "ini",
+            Format::Dotenv => "env",
+            Format::Hcl => "hcl",
+            Format::Json5 => "json5",
+            Format::Jsonc => "jsonc",
+            Format::Jsonl => "jsonl",
+            Format::Msgpack => "msgpack",
+            Format::Cbor => "cbor",
+            Format::Bson => "bson",
+            Format::Textproto => "textproto",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(feature = "cli")]
+impl clap::ValueEnum for Format {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Format::Json,
+            Format::Yaml,
+            Format::Toml,
+            Format::JavaProperties,
+            Format::Protobuf,
+            Format::Csv,
+            Format::Tsv,
+            Format::Xml,
+            Format::Ini,
+            Format::Dotenv,
+            Format::Hcl,
+            Format::Json5,
+            Format::Jsonc,
+            Format::Jsonl,
+            Format::Msgpack,
+            Format::Cbor,
+            Format::Bson,
+            Format::Textproto,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let help = match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+            Format::JavaProperties => "Java Properties",
+            Format::Protobuf => "Protocol Buffers",
+            Format::Csv => "Comma-separated values",
+            Format::Tsv => "Tab-separated values",
+            Format::Xml => "XML",
+            Format::Ini => "INI",
+            Format::Dotenv => "dotenv (.env)",
+            Format::Hcl => "HashiCorp Configuration Language",
+            Format::Json5 => "JSON5, written as plain JSON",
+            Format::Jsonc => "JSON with comments, written as plain JSON",
+            Format::Jsonl => "JSON Lines",
+            Format::Msgpack => "MessagePack",
+            Format::Cbor => "CBOR",
+            Format::Bson => "BSON",
+            Format::Textproto => "Protocol Buffers text format",
+        };
+        Some(clap::builder::PossibleValue::new(self.name()).help(help))
+    }
+}
+
+/// Reads a document of the given format.
+pub fn read(
+    input: impl Read,
+    format: &Format,
+    options: &options::ConvertOptions,
+) -> Result<Value, anyhow::Error> {
+    match format {
+        Format::Json => formats::json::read(input),
+        Format::Yaml => formats::yaml::read(input, &options.yaml_in),
+        Format::Toml => formats::toml::read(input),
+        Format::JavaProperties => formats::java_properties::read(input),
+        Format::Protobuf => formats::protobuf::read(input, &options.protobuf_in),
+        Format::Csv => formats::csv::read(input, b',', &options.csv_in),
+        Format::Tsv => formats::csv::read(input, b'\t', &options.csv_in),
+        Format::Xml => formats::xml::read(input, &options.xml_in),
+        Format::Ini => formats::ini::read(input, &options.ini_in),
+        Format::Dotenv => formats::dotenv::read(input),
+        Format::Hcl => formats::hcl::read(input),
+        Format::Json5 | Format::Jsonc => formats::json5::read(input),
+        Format::Jsonl => formats::jsonl::read(input),
+        Format::Msgpack => formats::msgpack::read(input),
+        Format::Cbor => formats::cbor::read(input),
+        Format::Bson => formats::bson::read(input, &options.bson_in),
+        Format::Textproto => formats::textproto::read(input, &options.protobuf_in),
+    }
+}
+
+/// Writes a document in the given format.
+pub fn write(
+    value: &Value,
+    output: impl Write,
+    format: &Format,
+    options: &options::ConvertOptions,
+) -> Result<(), anyhow::Error> {
+    match format {
+        Format::Json => formats::json::write(value, output),
+        Format::Yaml => formats::yaml::write(value, output),
+        Format::Toml => formats::toml::write(value, output),
+        Format::JavaProperties => {
+            formats::java_properties::write(value, output, &options.java_properties_out)
+        }
+        Format::Protobuf => formats::protobuf::write(value, output, &options.protobuf_out),
+        Format::Csv => formats::csv::write(value, output, b','),
+        Format::Tsv => formats::csv::write(value, output, b'\t'),
+        Format::Xml => formats::xml::write(value, output, &options.xml_out),
+        Format::Ini => formats::ini::write(value, output, &options.ini_out),
+        Format::Dotenv => formats::dotenv::write(value, output, &options.dotenv_out),
+        Format::Hcl => formats::hcl::write(value, output, &options.hcl_out),
+        Format::Json5 | Format::Jsonc => formats::json5::write(value, output),
+        Format::Jsonl => formats::jsonl::write(value, output),
+        Format::Msgpack => formats::msgpack::write(value, output),
+        Format::Cbor => formats::cbor::write(value, output),
+        Format::Bson => formats::bson::write(value, output),
+        Format::Textproto => formats::textproto::write(value, output, &options.protobuf_out),
+    }
+}
+
+/// Lazily read sequence of documents.
+pub type Documents<'a> = Box<dyn Iterator<Item = Result<Value, anyhow::Error>> + 'a>;
+
+fn into_documents(value: Value) -> Documents<'static> {
+    match value {
+        Value::Array(x) => Box::new(x.into_iter().map(Ok)),
+        x => Box::new(std::iter::once(Ok(x))),
+    }
+}
+
+/// Reads a sequence of documents of the given format.
+///
+/// JSON Lines, BSON dumps and length-delimited Protobuf are read one document
+/// at a time, YAML yields its `---` separated documents. Other formats, as well as YAML
+/// with a single document, yield elements of the top-level array or the whole
+/// document.
+pub fn read_documents<'a>(
+    input: impl Read + 'a,
+    format: &Format,
+    options: &options::ConvertOptions,
+) -> Result<Documents<'a>, anyhow::Error> {
+    match format {
+        Format::Jsonl => Ok(Box::new(formats::jsonl::read_documents(input))),
+        Format::Bson => Ok(Box::new(formats::bson::read_documents(
+            input,
+            &options.bson_in,
+        ))),
+        Format::Protobuf => Ok(Box::new(formats::protobuf::read_delimited(
+            input,
+            &options.protobuf_in,
+        )?)),
+        Format::Yaml => {
+            let mut documents = formats::yaml::read_documents(input, &options.yaml_in)?;
+            if documents.len() == 1 {
+                Ok(into_documents(documents.pop().unwrap()))
+            } else {
+                Ok(Box::new(documents.into_iter().map(Ok)))
+            }
+        }
+        x => Ok(into_documents(read(input, x, options)?)),
+    }
+}
+
+/// Writes a sequence of documents in the given format.
+///
+/// JSON Lines, YAML stream, JSON array, BSON dump and length-delimited
+/// Protobuf are written one document at a time, other formats get all documents as a
+/// single array.
+pub fn write_documents(
+    documents: Documents,
+    output: impl Write,
+    format: &Format,
+    options: &options::ConvertOptions,
+) -> Result<(), anyhow::Error> {
+    match format {
+        Format::Jsonl => formats::jsonl::write_documents(documents, output),
+        Format::Bson => formats::bson::write_documents(documents, output),
+        Format::Yaml => formats::yaml::write_documents(documents, output),
+        Format::Json => formats::json::write_documents(documents, output),
+        Format::Protobuf => {
+            formats::protobuf::write_delimited(documents, output, &options.protobuf_out)
+        }
+        x => {
+            let value = Value::Array(documents.collect::<Result<_, _>>()?);
+            write(&value, output, x, options)
+        }
+    }
+}
+
+/// Converts `input` of `input_format` into `output_format` and writes it to `output`.
+///
+/// Input is copied as is when both formats are the same, except for Protobuf
+/// which is re-encoded from `protobuf_in` to `protobuf_out` schema, text format
+/// Protobuf with `protobuf_out` message set, and YAML with a single document
+/// selected by `yaml_in`. When either side is JSON Lines, documents are
+/// streamed one at a time, see [`read_documents`] and [`write_documents`].
+///
+/// Returns paths of fields dropped while re-encoding Protobuf messages, which
+/// have no compatible field in `protobuf_out` schema or are unknown to
+/// `protobuf_in` schema. Other conversions drop nothing.
+pub fn convert(
+    input: impl Read,
+    input_format: Format,
+    output: impl Write,
+    output_format: Format,
+    options: &options::ConvertOptions,
+) -> Result<Vec<String>, anyhow::Error> {
+    match (&input_format, &output_format) {
+        (Format::Protobuf, Format::Protobuf) => {
+            formats::protobuf::transcode(input, output, &options.protobuf_in, &options.protobuf_out)
+        }
+        (Format::Textproto, Format::Textproto) if options.protobuf_out.get_message().is_some() => {
+            formats::textproto::transcode(
+                input,
+                output,
+                &options.protobuf_in,
+                &options.protobuf_out,
+            )
+        }
+        (Format::Yaml, Format::Yaml) if options.yaml_in.get_document().is_some() => {
+            let value = read(input, &input_format, options)?;
+            write(&value, output, &output_format, options)?;
+            Ok(Vec::new())
+        }
+        (x, y) if x == y => {
+            formats::just_write(input, output)?;
+            Ok(Vec::new())
+        }
+        (Format::Jsonl, _) | (_, Format::Jsonl) => {
+            let documents = read_documents(input, &input_format, options)?;
+            write_documents(documents, output, &output_format, options)?;
+            Ok(Vec::new())
+        }
+        _ => {
+            let value = read(input, &input_format, options)?;
+            write(&value, output, &output_format, options)?;
+            Ok(Vec::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path_detects_dotenv_files() {
+        let format = |x: &str| Format::from_path(Path::new(x));
+        assert_eq!(format(".env"), Some(Format::Dotenv));
+        assert_eq!(format("config/.env.local"), Some(Format::Dotenv));
+        assert_eq!(format("app.env"), Some(Format::Dotenv));
+        assert_eq!(format(".env.json"), Some(Format::Json));
+        assert_eq!(format(".env.yaml"), Some(Format::Yaml));
+        assert_eq!(format(".envrc"), None);
+    }
+}
@@ -1,14 +1,17 @@
-use std::{fs::File, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
 
 use anyhow::anyhow;
-use clap::{Parser, ValueEnum, builder::PossibleValue};
+use clap::Parser;
 
-use transformations::*;
-
-use options::Options as _;
-
-mod options;
-mod transformations;
+use cvto::{
+    Format, detect,
+    options::{self, ConvertOptions, Options as _},
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "cvto")]
@@ -18,12 +21,26 @@ mod transformations;
 - YAML
 - TOML
 - Java Properties
-- Protobuf"#, long_about = None)]
+- Protobuf
+- CSV
+- TSV
+- XML
+- INI
+- dotenv
+- HCL
+- JSON5 and JSONC (input only)
+- JSON Lines
+- MessagePack
+- CBOR
+- BSON
+- Protobuf text format"#, long_about = None)]
 struct Cli {
-    #[arg(help = "Path to input file")]
-    input: PathBuf,
-    #[arg(help = "Path to output file")]
-    output: PathBuf,
+    #[arg(help = "Path to input file, standard input if omitted or '-'")]
+    input: Option<PathBuf>,
+    #[arg(
+        help = "Path to output file, standard output if omitted or '-'. Every document is written to its own file if path contains {index}"
+    )]
+    output: Option<PathBuf>,
 
     #[arg(short = 'i', help = "Format of input file", display_order = 0)]
     input_format: Option<Format>,
@@ -31,6 +48,58 @@ struct Cli {
     #[arg(short = 'o', help = "Format of output file", display_order = 1)]
     output_format: Option<Format>,
 
+    #[arg(
+        long,
+        value_name = "MODE",
+        help = "Mode of Extended JSON that BSON values are read as [default: relaxed]"
+    )]
+    bson_in_extended_json: Option<options::bson::ExtendedJson>,
+
+    #[arg(long, help = "Infer booleans, numbers and nulls from cell values")]
+    csv_in_infer_types: bool,
+
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Separator of nested keys [default: __]"
+    )]
+    dotenv_out_separator: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAMES",
+        help = "Keys written as blocks, with number of label levels after colon, like resource:2"
+    )]
+    hcl_out_blocks: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        value_name = "CHARS",
+        help = "Characters that start a comment [default: ;#]"
+    )]
+    ini_in_comment_chars: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "POLICY",
+        help = "What to do with keys repeated in a section [default: last]"
+    )]
+    ini_in_duplicate_keys: Option<options::ini::DuplicateKeys>,
+
+    #[arg(
+        long,
+        value_name = "STYLE",
+        help = "Which section names are split into nested objects [default: quoted]"
+    )]
+    ini_in_subsections: Option<options::ini::Subsections>,
+
+    #[arg(
+        long,
+        value_name = "POLICY",
+        help = "What to do with objects nested deeper than section and key [default: reject]"
+    )]
+    ini_out_nesting: Option<options::ini::Nesting>,
+
     #[arg(
         long,
         value_name = "VALUE",
@@ -45,6 +114,13 @@ struct Cli {
     )]
     protobuf_in_input: Option<Vec<String>>,
 
+    #[arg(
+        long,
+        value_name = "FILES",
+        help = "Paths to binary FileDescriptorSet files, as produced by protoc --descriptor_set_out"
+    )]
+    protobuf_in_descriptor_set: Option<Vec<String>>,
+
     #[arg(
         long,
         value_name = "DIRS",
@@ -55,6 +131,12 @@ struct Cli {
     #[arg(long, value_name = "VALUE", help = "Name of the target message type")]
     protobuf_in_message: Option<String>,
 
+    #[arg(
+        long,
+        help = "Decode wire format without schema, like protoc --decode_raw"
+    )]
+    protobuf_in_raw: bool,
+
     #[arg(
         long,
         value_name = "FILES",
@@ -62,6 +144,13 @@ struct Cli {
     )]
     protobuf_out_input: Option<Vec<String>>,
 
+    #[arg(
+        long,
+        value_name = "FILES",
+        help = "Paths to binary FileDescriptorSet files, as produced by protoc --descriptor_set_out"
+    )]
+    protobuf_out_descriptor_set: Option<Vec<String>>,
+
     #[arg(
         long,
         value_name = "DIRS",
@@ -71,166 +160,532 @@ struct Cli {
 
     #[arg(long, value_name = "VALUE", help = "Name of the target message type")]
     protobuf_out_message: Option<String>,
-}
 
-#[derive(Clone, Debug)]
-enum Format {
-    Json,
-    Yaml,
-    Toml,
-    JavaProperties,
-    Protobuf,
-}
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Prefix of keys that hold attributes [default: @]"
+    )]
+    xml_in_attribute_prefix: Option<String>,
 
-impl ValueEnum for Format {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Format::Json,
-            Format::Yaml,
-            Format::Toml,
-            Format::JavaProperties,
-            Format::Protobuf,
-        ]
-    }
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Key that holds text of elements with attributes or children [default: #text]"
+    )]
+    xml_in_text_key: Option<String>,
 
-    fn to_possible_value(&self) -> Option<PossibleValue> {
-        match self {
-            Format::Json => Some(PossibleValue::new("json").help("JSON")),
-            Format::Yaml => Some(PossibleValue::new("yaml").help("YAML")),
-            Format::Toml => Some(PossibleValue::new("toml").help("TOML")),
-            Format::JavaProperties => {
-                Some(PossibleValue::new("properties").help("Java Properties"))
-            }
-            Format::Protobuf => Some(PossibleValue::new("protobuf").help("Protocol Buffers")),
-        }
-    }
+    #[arg(
+        long,
+        value_name = "ELEMENTS",
+        help = "Names of elements that are always read as arrays"
+    )]
+    xml_in_array: Option<Vec<String>>,
+
+    #[arg(long, help = "Remove namespace prefixes and declarations")]
+    xml_in_strip_namespaces: bool,
+
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Prefix of keys that hold attributes [default: @]"
+    )]
+    xml_out_attribute_prefix: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Key that holds text of elements with attributes or children [default: #text]"
+    )]
+    xml_out_text_key: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "VALUE",
+        help = "Name of the root element if document does not have single root [default: root]"
+    )]
+    xml_out_root: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "INDEX",
+        help = "Index of the only document to read from multi-document stream, starting from 0, not counting empty documents"
+    )]
+    yaml_in_document: Option<usize>,
 }
 
-fn determine_format(path: &PathBuf) -> Option<Format> {
-    let extension = path.extension()?;
-    let extension = extension.to_string_lossy().into_owned();
-    match extension.as_str() {
-        "json" => Some(Format::Json),
-        "yaml" => Some(Format::Yaml),
-        "yml" => Some(Format::Yaml),
-        "toml" => Some(Format::Toml),
-        "properties" => Some(Format::JavaProperties),
-        "protobuf" => Some(Format::Protobuf),
-        _ => None,
-    }
+const SNIFF_LIMIT: u64 = 8192;
+const INDEX_PLACEHOLDER: &str = "{index}";
+
+fn get_path(path: &Option<PathBuf>) -> Option<&Path> {
+    path.as_deref().filter(|x| x.as_os_str() != "-")
 }
 
-fn get_format(path: &PathBuf, format: Option<Format>) -> Result<Format, anyhow::Error> {
+fn get_format(
+    path: Option<&Path>,
+    format: Option<Format>,
+    flag: &str,
+) -> Result<Format, anyhow::Error> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+    let Some(path) = path else {
+        return Err(anyhow!(
+            "Format cannot be determined for standard stream, set it with {}",
+            flag
+        ));
+    };
+    let format = Format::from_path(path);
     if let Some(format) = format {
         Ok(format)
     } else {
-        let format = determine_format(path);
-        if let Some(format) = format {
-            Ok(format)
-        } else {
-            Err(anyhow!(
-                "Format cannot be determined for file: {}",
-                path.as_path().to_string_lossy()
-            ))
-        }
+        Err(anyhow!(
+            "Format cannot be determined for file: {}",
+            path.to_string_lossy()
+        ))
     }
 }
 
-fn main() -> Result<(), anyhow::Error> {
-    let cli = Cli::parse();
+/// Determines input format like [`get_format`]. Files with `.conf` extension
+/// have no common syntax, so they are read as INI only if some of INI input
+/// options is set.
+fn get_input_format(cli: &Cli, path: Option<&Path>) -> Result<Format, anyhow::Error> {
+    get_format(path, cli.input_format.clone(), "-i").or_else(|error| {
+        let is_conf = path
+            .and_then(|x| x.extension())
+            .is_some_and(|x| x == "conf");
+        let has_ini_options = cli.ini_in_comment_chars.is_some()
+            || cli.ini_in_duplicate_keys.is_some()
+            || cli.ini_in_subsections.is_some();
+        if is_conf && has_ini_options {
+            Ok(Format::Ini)
+        } else {
+            Err(error)
+        }
+    })
+}
 
-    let input_format = get_format(&cli.input, cli.input_format)?;
-    let output_format = get_format(&cli.output, cli.output_format)?;
-    let input = File::open(cli.input.as_path()).map_err(|x| {
-        anyhow!(
-            "Error while opening file {}: {}",
-            cli.input.to_string_lossy(),
-            x
-        )
-    })?;
-    let output = File::create(cli.output.as_path()).map_err(|x| {
-        anyhow!(
-            "Error while creating file {}: {}",
-            cli.input.to_string_lossy(),
-            x
-        )
-    })?;
-
-    let mut protobuf_in_options = options::protobuf::InOptions::new();
+fn get_options(cli: &Cli, input_format: &Format, output_format: &Format) -> ConvertOptions {
+    let mut convert_options = ConvertOptions::default();
     match input_format {
         Format::Json => {}
-        Format::Yaml => {}
+        Format::Yaml => {
+            if let Some(x) = cli.yaml_in_document {
+                convert_options
+                    .yaml_in
+                    .set(options::yaml::InOption::Document(x));
+            }
+        }
         Format::Toml => {}
         Format::JavaProperties => {}
-        Format::Protobuf => {
-            if let Some(x) = cli.protobuf_in_include {
-                protobuf_in_options.set(options::protobuf::InOption::Include(x));
+        Format::Dotenv => {}
+        Format::Hcl => {}
+        Format::Json5 | Format::Jsonc => {}
+        Format::Jsonl => {}
+        Format::Msgpack => {}
+        Format::Cbor => {}
+        Format::Bson => {
+            if let Some(x) = cli.bson_in_extended_json {
+                convert_options
+                    .bson_in
+                    .set(options::bson::InOption::ExtendedJson(x));
             }
-            if let Some(x) = cli.protobuf_in_input {
-                protobuf_in_options.set(options::protobuf::InOption::Input(x));
+        }
+        Format::Csv | Format::Tsv => {
+            if cli.csv_in_infer_types {
+                convert_options
+                    .csv_in
+                    .set(options::csv::InOption::InferTypes(true));
+            }
+        }
+        Format::Protobuf | Format::Textproto => {
+            if let Some(x) = cli.protobuf_in_descriptor_set.clone() {
+                convert_options
+                    .protobuf_in
+                    .set(options::protobuf::InOption::DescriptorSet(x));
+            }
+            if let Some(x) = cli.protobuf_in_include.clone() {
+                convert_options
+                    .protobuf_in
+                    .set(options::protobuf::InOption::Include(x));
+            }
+            if let Some(x) = cli.protobuf_in_input.clone() {
+                convert_options
+                    .protobuf_in
+                    .set(options::protobuf::InOption::Input(x));
+            }
+            if let Some(x) = cli.protobuf_in_message.clone() {
+                convert_options
+                    .protobuf_in
+                    .set(options::protobuf::InOption::Message(x));
             }
-            if let Some(x) = cli.protobuf_in_message {
-                protobuf_in_options.set(options::protobuf::InOption::Message(x));
+            if cli.protobuf_in_raw {
+                convert_options
+                    .protobuf_in
+                    .set(options::protobuf::InOption::Raw(true));
             }
         }
-    };
+        Format::Ini => {
+            if let Some(x) = cli.ini_in_comment_chars.clone() {
+                convert_options
+                    .ini_in
+                    .set(options::ini::InOption::CommentChars(x));
+            }
+            if let Some(x) = cli.ini_in_duplicate_keys {
+                convert_options
+                    .ini_in
+                    .set(options::ini::InOption::DuplicateKeys(x));
+            }
+            if let Some(x) = cli.ini_in_subsections {
+                convert_options
+                    .ini_in
+                    .set(options::ini::InOption::Subsections(x));
+            }
+        }
+        Format::Xml => {
+            if let Some(x) = cli.xml_in_attribute_prefix.clone() {
+                convert_options
+                    .xml_in
+                    .set(options::xml::InOption::AttributePrefix(x));
+            }
+            if let Some(x) = cli.xml_in_text_key.clone() {
+                convert_options
+                    .xml_in
+                    .set(options::xml::InOption::TextKey(x));
+            }
+            if let Some(x) = cli.xml_in_array.clone() {
+                convert_options.xml_in.set(options::xml::InOption::Array(x));
+            }
+            if cli.xml_in_strip_namespaces {
+                convert_options
+                    .xml_in
+                    .set(options::xml::InOption::StripNamespaces(true));
+            }
+        }
+    }
 
-    let mut java_properties_out_options = options::java_properties::OutOptions::new();
-    let mut protobuf_out_options = options::protobuf::OutOptions::new();
     match output_format {
         Format::Json => {}
         Format::Yaml => {}
         Format::Toml => {}
+        Format::Csv => {}
+        Format::Tsv => {}
+        Format::Hcl => {
+            if let Some(x) = cli.hcl_out_blocks.clone() {
+                convert_options
+                    .hcl_out
+                    .set(options::hcl::OutOption::Blocks(x));
+            }
+        }
+        Format::Json5 | Format::Jsonc => {}
+        Format::Jsonl => {}
+        Format::Msgpack => {}
+        Format::Cbor => {}
+        Format::Bson => {}
+        Format::Dotenv => {
+            if let Some(x) = cli.dotenv_out_separator.clone() {
+                convert_options
+                    .dotenv_out
+                    .set(options::dotenv::OutOption::Separator(x));
+            }
+        }
+        Format::Ini => {
+            if let Some(x) = cli.ini_out_nesting {
+                convert_options
+                    .ini_out
+                    .set(options::ini::OutOption::Nesting(x));
+            }
+        }
         Format::JavaProperties => {
-            if let Some(x) = cli.java_properties_out_kv_separator {
-                java_properties_out_options
+            if let Some(x) = cli.java_properties_out_kv_separator.clone() {
+                convert_options
+                    .java_properties_out
                     .set(options::java_properties::OutOption::KvSeparator(x));
             }
         }
-        Format::Protobuf => {
-            if let Some(x) = cli.protobuf_out_include {
-                protobuf_out_options.set(options::protobuf::OutOption::Include(x));
+        Format::Protobuf | Format::Textproto => {
+            if let Some(x) = cli.protobuf_out_descriptor_set.clone() {
+                convert_options
+                    .protobuf_out
+                    .set(options::protobuf::OutOption::DescriptorSet(x));
+            }
+            if let Some(x) = cli.protobuf_out_include.clone() {
+                convert_options
+                    .protobuf_out
+                    .set(options::protobuf::OutOption::Include(x));
+            }
+            if let Some(x) = cli.protobuf_out_input.clone() {
+                convert_options
+                    .protobuf_out
+                    .set(options::protobuf::OutOption::Input(x));
+            }
+            if let Some(x) = cli.protobuf_out_message.clone() {
+                convert_options
+                    .protobuf_out
+                    .set(options::protobuf::OutOption::Message(x));
+            }
+        }
+        Format::Xml => {
+            if let Some(x) = cli.xml_out_attribute_prefix.clone() {
+                convert_options
+                    .xml_out
+                    .set(options::xml::OutOption::AttributePrefix(x));
+            }
+            if let Some(x) = cli.xml_out_text_key.clone() {
+                convert_options
+                    .xml_out
+                    .set(options::xml::OutOption::TextKey(x));
+            }
+            if let Some(x) = cli.xml_out_root.clone() {
+                convert_options
+                    .xml_out
+                    .set(options::xml::OutOption::Root(x));
+            }
+        }
+    }
+
+    convert_options
+}
+
+fn is_batch(path: &Path) -> bool {
+    path.is_dir() || path.to_string_lossy().contains(['*', '?', '['])
+}
+
+fn get_batch_inputs(pattern: &Path) -> Result<(PathBuf, Vec<PathBuf>), anyhow::Error> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+        let mut entries = std::fs::read_dir(dir)?
+            .map(|x| x.map(|x| x.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() {
+                walk(&entry, files)?;
+            } else {
+                files.push(entry);
             }
-            if let Some(x) = cli.protobuf_out_input {
-                protobuf_out_options.set(options::protobuf::OutOption::Input(x));
+        }
+        Ok(())
+    }
+
+    if pattern.is_dir() {
+        let mut files = Vec::new();
+        walk(pattern, &mut files)?;
+        return Ok((pattern.to_path_buf(), files));
+    }
+    let base: PathBuf = pattern
+        .components()
+        .take_while(|x| !x.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect();
+    let mut files = Vec::new();
+    for entry in glob::glob(&pattern.to_string_lossy())? {
+        let entry = entry?;
+        if entry.is_file() {
+            files.push(entry);
+        }
+    }
+    Ok((base, files))
+}
+
+fn report_dropped(dropped: &[String], path: Option<&Path>) {
+    for field in dropped {
+        // Fields unknown to the input schema are reported by number.
+        let name = field.rsplit('.').next().unwrap_or(field);
+        let reason = if name.parse::<u32>().is_ok() {
+            "unknown field in input message"
+        } else {
+            "no compatible field in target message"
+        };
+        match path {
+            Some(path) => eprintln!(
+                "Field {} of {} is dropped: {}",
+                field,
+                path.to_string_lossy(),
+                reason
+            ),
+            None => eprintln!("Field {} is dropped: {}", field, reason),
+        }
+    }
+}
+
+fn convert_file(
+    input_path: &Path,
+    input_format: Format,
+    output_path: &Path,
+    output_format: Format,
+    convert_options: &ConvertOptions,
+) -> Result<Vec<String>, anyhow::Error> {
+    let input = File::open(input_path)?;
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut output = BufWriter::new(File::create(output_path)?);
+    let result = cvto::convert(
+        input,
+        input_format,
+        &mut output,
+        output_format,
+        convert_options,
+    )
+    .and_then(|dropped| {
+        output.flush()?;
+        Ok(dropped)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(output_path);
+    }
+    result
+}
+
+fn run_batch(cli: &Cli, input: &Path, output: Option<&Path>) -> Result<(), anyhow::Error> {
+    let Some(output) = output else {
+        return Err(anyhow!("Output directory is required for batch conversion"));
+    };
+    let Some(output_format) = cli.output_format.clone() else {
+        return Err(anyhow!(
+            "Output format must be set with -o for batch conversion"
+        ));
+    };
+    let (base, files) = get_batch_inputs(input)?;
+    let mut converted = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    let mut targets = HashMap::new();
+    for file in files {
+        let Ok(input_format) = get_input_format(cli, Some(&file)) else {
+            skipped += 1;
+            continue;
+        };
+        let relative = file.strip_prefix(&base).unwrap_or(&file);
+        let target = output
+            .join(relative)
+            .with_extension(output_format.extension());
+        if let Some(previous) = targets.insert(target.clone(), file.clone()) {
+            eprintln!(
+                "Failed to convert {}: {} is already written from {}",
+                file.to_string_lossy(),
+                target.to_string_lossy(),
+                previous.to_string_lossy()
+            );
+            failed += 1;
+            continue;
+        }
+        let convert_options = get_options(cli, &input_format, &output_format);
+        match convert_file(
+            &file,
+            input_format,
+            &target,
+            output_format.clone(),
+            &convert_options,
+        ) {
+            Ok(dropped) => {
+                report_dropped(&dropped, Some(&file));
+                converted += 1;
             }
-            if let Some(x) = cli.protobuf_out_message {
-                protobuf_out_options.set(options::protobuf::OutOption::Message(x));
+            Err(x) => {
+                eprintln!("Failed to convert {}: {}", file.to_string_lossy(), x);
+                failed += 1;
             }
         }
     }
+    eprintln!(
+        "Converted: {}, failed: {}, skipped: {}",
+        converted, failed, skipped
+    );
+    if failed > 0 {
+        return Err(anyhow!("{} files failed to convert", failed));
+    }
+    Ok(())
+}
+
+fn split_documents(
+    input: impl Read,
+    input_format: Format,
+    template: &Path,
+    output_format: Format,
+    convert_options: &ConvertOptions,
+) -> Result<(), anyhow::Error> {
+    let template = template.to_string_lossy();
+    let documents = cvto::read_documents(input, &input_format, convert_options)?;
+    for (index, value) in documents.enumerate() {
+        let path = PathBuf::from(template.replace(INDEX_PLACEHOLDER, &index.to_string()));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut output = BufWriter::new(File::create(&path).map_err(|x| {
+            anyhow!(
+                "Error while creating file {}: {}",
+                path.to_string_lossy(),
+                x
+            )
+        })?);
+        cvto::write(&value?, &mut output, &output_format, convert_options)?;
+        output.flush()?;
+    }
+    Ok(())
+}
 
-    match (input_format, output_format) {
-        (Format::Json, Format::Json) => just_write(input, output)?,
-        (Format::Json, Format::Yaml) => json_to_yaml(input, output)?,
-        (Format::Json, Format::Toml) => json_to_toml(input, output)?,
-        (Format::Json, Format::JavaProperties) => {
-            json_to_properties(input, output, java_properties_out_options)?
-        }
-        (Format::Json, Format::Protobuf) => json_to_protobuf(input, output, protobuf_out_options)?,
-        (Format::Yaml, Format::Json) => yaml_to_json(input, output)?,
-        (Format::Yaml, Format::Yaml) => just_write(input, output)?,
-        (Format::Yaml, Format::Toml) => yaml_to_toml(input, output)?,
-        (Format::Yaml, Format::JavaProperties) => {
-            yaml_to_properties(input, output, java_properties_out_options)?
-        }
-        (Format::Yaml, Format::Protobuf) => todo!(),
-        (Format::Toml, Format::Json) => toml_to_json(input, output)?,
-        (Format::Toml, Format::Yaml) => toml_to_yaml(input, output)?,
-        (Format::Toml, Format::Toml) => just_write(input, output)?,
-        (Format::Toml, Format::JavaProperties) => toml_to_properties(input, output)?,
-        (Format::Toml, Format::Protobuf) => todo!(),
-        (Format::JavaProperties, Format::Json) => properties_to_json(input, output)?,
-        (Format::JavaProperties, Format::Yaml) => properties_to_yaml(input, output)?,
-        (Format::JavaProperties, Format::Toml) => properties_to_toml(input, output)?,
-        (Format::JavaProperties, Format::JavaProperties) => just_write(input, output)?,
-        (Format::JavaProperties, Format::Protobuf) => todo!(),
-        (Format::Protobuf, Format::Json) => todo!(),
-        (Format::Protobuf, Format::Yaml) => todo!(),
-        (Format::Protobuf, Format::Toml) => protobuf_to_toml(input, output, protobuf_in_options)?,
-        (Format::Protobuf, Format::JavaProperties) => todo!(),
-        (Format::Protobuf, Format::Protobuf) => todo!(),
+fn main() -> Result<(), anyhow::Error> {
+    let cli = Cli::parse();
+
+    let input_path = get_path(&cli.input);
+    let output_path = get_path(&cli.output);
+    if let Some(path) = input_path.filter(|x| is_batch(x)) {
+        return run_batch(&cli, path, output_path);
+    }
+    let output_format = get_format(output_path, cli.output_format.clone(), "-o")?;
+    let mut input: Box<dyn Read> =
+        if let Some(path) = input_path {
+            Box::new(File::open(path).map_err(|x| {
+                anyhow!("Error while opening file {}: {}", path.to_string_lossy(), x)
+            })?)
+        } else {
+            Box::new(io::stdin().lock())
+        };
+    let input_format = match get_input_format(&cli, input_path) {
+        Ok(x) => x,
+        Err(error) => {
+            let mut prefix = Vec::new();
+            (&mut input).take(SNIFF_LIMIT).read_to_end(&mut prefix)?;
+            let complete = (prefix.len() as u64) < SNIFF_LIMIT;
+            let Some(detection) = detect::sniff(&prefix, complete) else {
+                return Err(error);
+            };
+            eprintln!(
+                "Detected input format: {} (confidence: {})",
+                detection.format, detection.confidence
+            );
+            input = Box::new(Cursor::new(prefix).chain(input));
+            detection.format
+        }
     };
+    if let Some(path) = output_path.filter(|x| x.to_string_lossy().contains(INDEX_PLACEHOLDER)) {
+        let convert_options = get_options(&cli, &input_format, &output_format);
+        return split_documents(input, input_format, path, output_format, &convert_options);
+    }
+    let output: Box<dyn Write> = if let Some(path) = output_path {
+        Box::new(File::create(path).map_err(|x| {
+            anyhow!(
+                "Error while creating file {}: {}",
+                path.to_string_lossy(),
+                x
+            )
+        })?)
+    } else {
+        Box::new(io::stdout().lock())
+    };
+
+    let mut output = BufWriter::new(output);
+
+    let convert_options = get_options(&cli, &input_format, &output_format);
+    let dropped = cvto::convert(
+        input,
+        input_format,
+        &mut output,
+        output_format,
+        &convert_options,
+    )?;
+    output.flush()?;
+    report_dropped(&dropped, None);
 
     Ok(())
 }
@@ -1,7 +1,404 @@
+/// Collection of options that may be set one by one.
 pub trait Options<Opt> {
     fn set(&mut self, option: Opt);
 }
 
+/// Options of every format used by [`crate::convert`].
+#[derive(Default)]
+pub struct ConvertOptions {
+    pub bson_in: bson::InOptions,
+    pub csv_in: csv::InOptions,
+    pub dotenv_out: dotenv::OutOptions,
+    pub hcl_out: hcl::OutOptions,
+    pub ini_in: ini::InOptions,
+    pub ini_out: ini::OutOptions,
+    pub java_properties_out: java_properties::OutOptions,
+    pub protobuf_in: protobuf::InOptions,
+    pub protobuf_out: protobuf::OutOptions,
+    pub xml_in: xml::InOptions,
+    pub xml_out: xml::OutOptions,
+    pub yaml_in: yaml::InOptions,
+}
+
+pub mod bson {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const EXTENDED_JSON: &str = "extended_json";
+
+    /// Mode of MongoDB Extended JSON that BSON values are read as.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+    pub enum ExtendedJson {
+        /// Use native JSON numbers and ISO-8601 dates where possible
+        #[default]
+        Relaxed,
+        /// Preserve every BSON type, including numeric ones
+        Canonical,
+    }
+
+    /// Option of reading BSON.
+    #[derive(Debug)]
+    pub enum InOption {
+        /// Mode of Extended JSON that BSON values are read as, relaxed by default.
+        ExtendedJson(ExtendedJson),
+    }
+
+    /// Set options, see [`InOption`].
+    pub struct InOptions {
+        inner: HashMap<String, InOption>,
+    }
+
+    impl InOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_extended_json(&self) -> ExtendedJson {
+            self.inner
+                .get(EXTENDED_JSON)
+                .map(|x| match x {
+                    InOption::ExtendedJson(x) => *x,
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<InOption> for InOptions {
+        fn set(&mut self, option: InOption) {
+            let name = match option {
+                InOption::ExtendedJson(_) => EXTENDED_JSON,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod csv {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const INFER_TYPES: &str = "infer_types";
+
+    /// Option of reading CSV and TSV.
+    #[derive(Debug)]
+    pub enum InOption {
+        /// Read booleans, numbers and nulls from cell values instead of strings.
+        InferTypes(bool),
+    }
+
+    /// Set options, see [`InOption`].
+    pub struct InOptions {
+        inner: HashMap<String, InOption>,
+    }
+
+    impl InOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_infer_types(&self) -> bool {
+            self.inner
+                .get(INFER_TYPES)
+                .map(|x| match x {
+                    InOption::InferTypes(x) => *x,
+                })
+                .unwrap_or(false)
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<InOption> for InOptions {
+        fn set(&mut self, option: InOption) {
+            let name = match option {
+                InOption::InferTypes(_) => INFER_TYPES,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod dotenv {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const SEPARATOR: &str = "separator";
+
+    /// Option of writing dotenv.
+    #[derive(Debug)]
+    pub enum OutOption {
+        /// Separator that joins keys of nested objects, `__` by default.
+        Separator(String),
+    }
+
+    /// Set options, see [`OutOption`].
+    pub struct OutOptions {
+        inner: HashMap<String, OutOption>,
+    }
+
+    impl OutOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_separator(&self) -> Option<&str> {
+            self.inner.get(SEPARATOR).map(|x| match x {
+                OutOption::Separator(x) => x.as_str(),
+            })
+        }
+    }
+
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<OutOption> for OutOptions {
+        fn set(&mut self, option: OutOption) {
+            let name = match option {
+                OutOption::Separator(_) => SEPARATOR,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod hcl {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const BLOCKS: &str = "blocks";
+
+    /// Option of writing HCL.
+    #[derive(Debug)]
+    pub enum OutOption {
+        /// Keys written as blocks instead of attributes, as `name` or `name:labels`
+        /// where `labels` is the number of label levels.
+        Blocks(Vec<String>),
+    }
+
+    /// Set options, see [`OutOption`].
+    pub struct OutOptions {
+        inner: HashMap<String, OutOption>,
+    }
+
+    impl OutOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_blocks(&self) -> Vec<String> {
+            self.inner
+                .get(BLOCKS)
+                .map(|x| match x {
+                    OutOption::Blocks(x) => x.clone(),
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<OutOption> for OutOptions {
+        fn set(&mut self, option: OutOption) {
+            let name = match option {
+                OutOption::Blocks(_) => BLOCKS,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod ini {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const COMMENT_CHARS: &str = "comment_chars";
+    const DUPLICATE_KEYS: &str = "duplicate_keys";
+    const SUBSECTIONS: &str = "subsections";
+    const NESTING: &str = "nesting";
+
+    /// What to do when a key occurs more than once in a section.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+    pub enum DuplicateKeys {
+        /// Keep the first value
+        First,
+        /// Keep the last value
+        #[default]
+        Last,
+        /// Fail to read the file
+        Error,
+        /// Collect all values into array
+        Array,
+    }
+
+    /// Which section names are split into nested objects.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+    pub enum Subsections {
+        /// Keep section names as is
+        None,
+        /// Split `[a "b"]` into section `a` and subsection `b`
+        #[default]
+        Quoted,
+        /// Split both `[a "b"]` and `[a.b]`
+        Dotted,
+    }
+
+    /// What to do with objects nested deeper than section and key.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+    pub enum Nesting {
+        /// Fail to write the file
+        #[default]
+        Reject,
+        /// Flatten nested keys of a section into dotted keys
+        Flatten,
+    }
+
+    /// Option of reading INI.
+    #[derive(Debug)]
+    pub enum InOption {
+        /// Characters that start a comment, `;#` by default.
+        CommentChars(String),
+        /// What to do with keys repeated in a section.
+        DuplicateKeys(DuplicateKeys),
+        /// Which section names are split into nested objects.
+        Subsections(Subsections),
+    }
+
+    /// Set options, see [`InOption`].
+    pub struct InOptions {
+        inner: HashMap<String, InOption>,
+    }
+
+    impl InOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_comment_chars(&self) -> Option<&str> {
+            self.inner.get(COMMENT_CHARS).map(|x| match x {
+                InOption::CommentChars(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+
+        pub fn get_duplicate_keys(&self) -> DuplicateKeys {
+            self.inner
+                .get(DUPLICATE_KEYS)
+                .map(|x| match x {
+                    InOption::DuplicateKeys(x) => *x,
+                    _ => unreachable!(),
+                })
+                .unwrap_or_default()
+        }
+
+        pub fn get_subsections(&self) -> Subsections {
+            self.inner
+                .get(SUBSECTIONS)
+                .map(|x| match x {
+                    InOption::Subsections(x) => *x,
+                    _ => unreachable!(),
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<InOption> for InOptions {
+        fn set(&mut self, option: InOption) {
+            let name = match option {
+                InOption::CommentChars(_) => COMMENT_CHARS,
+                InOption::DuplicateKeys(_) => DUPLICATE_KEYS,
+                InOption::Subsections(_) => SUBSECTIONS,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+
+    /// Option of writing INI.
+    #[derive(Debug)]
+    pub enum OutOption {
+        /// What to do with objects nested deeper than section and key.
+        Nesting(Nesting),
+    }
+
+    /// Set options, see [`OutOption`].
+    pub struct OutOptions {
+        inner: HashMap<String, OutOption>,
+    }
+
+    impl OutOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_nesting(&self) -> Nesting {
+            self.inner
+                .get(NESTING)
+                .map(|x| match x {
+                    OutOption::Nesting(x) => *x,
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<OutOption> for OutOptions {
+        fn set(&mut self, option: OutOption) {
+            let name = match option {
+                OutOption::Nesting(_) => NESTING,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
 pub mod java_properties {
     use std::collections::HashMap;
 
@@ -9,11 +406,14 @@ pub mod java_properties {
 
     const KV_SEPARATOR: &str = "kv_separator";
 
+    /// Option of writing Java Properties.
     #[derive(Debug)]
     pub enum OutOption {
+        /// Separator between key and value, `=` by default.
         KvSeparator(String),
     }
 
+    /// Set options, see [`OutOption`].
     pub struct OutOptions {
         inner: HashMap<String, OutOption>,
     }
@@ -32,6 +432,12 @@ pub mod java_properties {
         }
     }
 
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl Options<OutOption> for OutOptions {
         fn set(&mut self, option: OutOption) {
             let name = match option {
@@ -47,17 +453,27 @@ pub mod protobuf {
 
     use crate::options::Options;
 
+    const DESCRIPTOR_SET: &str = "descriptor_set";
     const INCLUDE: &str = "include";
     const INPUT: &str = "input";
     const MESSAGE: &str = "message";
+    const RAW: &str = "raw";
 
+    /// Option of writing Protobuf and its text format.
     #[derive(Debug)]
     pub enum OutOption {
+        /// Paths to binary `FileDescriptorSet` files, as produced by
+        /// `protoc --descriptor_set_out`.
+        DescriptorSet(Vec<String>),
+        /// Paths to directories that imports of `.proto` files are resolved against.
         Include(Vec<String>),
+        /// Paths to `.proto` files with the schema.
         Input(Vec<String>),
+        /// Name of the message type to write.
         Message(String),
     }
 
+    /// Set options, see [`OutOption`].
     pub struct OutOptions {
         inner: HashMap<String, OutOption>,
     }
@@ -69,6 +485,16 @@ pub mod protobuf {
             }
         }
 
+        pub fn get_descriptor_set(&self) -> Vec<String> {
+            self.inner
+                .get(DESCRIPTOR_SET)
+                .map(|x| match x {
+                    OutOption::DescriptorSet(y) => y.clone(),
+                    _ => unreachable!(),
+                })
+                .unwrap_or_default()
+        }
+
         pub fn get_include(&self) -> Vec<String> {
             self.inner
                 .get(INCLUDE)
@@ -76,7 +502,7 @@ pub mod protobuf {
                     OutOption::Include(y) => y.clone(),
                     _ => unreachable!(),
                 })
-                .unwrap_or(Vec::new())
+                .unwrap_or_default()
         }
 
         pub fn get_input(&self) -> Vec<String> {
@@ -86,7 +512,7 @@ pub mod protobuf {
                     OutOption::Input(y) => y.clone(),
                     _ => unreachable!(),
                 })
-                .unwrap_or(Vec::new())
+                .unwrap_or_default()
         }
 
         pub fn get_message(&self) -> Option<String> {
@@ -97,9 +523,16 @@ pub mod protobuf {
         }
     }
 
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl Options<OutOption> for OutOptions {
         fn set(&mut self, option: OutOption) {
             let name = match option {
+                OutOption::DescriptorSet(_) => DESCRIPTOR_SET,
                 OutOption::Include(_) => INCLUDE,
                 OutOption::Input(_) => INPUT,
                 OutOption::Message(_) => MESSAGE,
@@ -108,13 +541,23 @@ pub mod protobuf {
         }
     }
 
+    /// Option of reading Protobuf and its text format.
     #[derive(Debug)]
     pub enum InOption {
+        /// Paths to binary `FileDescriptorSet` files, as produced by
+        /// `protoc --descriptor_set_out`.
+        DescriptorSet(Vec<String>),
+        /// Paths to directories that imports of `.proto` files are resolved against.
         Include(Vec<String>),
+        /// Paths to `.proto` files with the schema.
         Input(Vec<String>),
+        /// Name of the message type to read.
         Message(String),
+        /// Decode wire format without schema, like `protoc --decode_raw`.
+        Raw(bool),
     }
 
+    /// Set options, see [`InOption`].
     pub struct InOptions {
         inner: HashMap<String, InOption>,
     }
@@ -126,6 +569,16 @@ pub mod protobuf {
             }
         }
 
+        pub fn get_descriptor_set(&self) -> Vec<String> {
+            self.inner
+                .get(DESCRIPTOR_SET)
+                .map(|x| match x {
+                    InOption::DescriptorSet(y) => y.clone(),
+                    _ => unreachable!(),
+                })
+                .unwrap_or_default()
+        }
+
         pub fn get_include(&self) -> Vec<String> {
             self.inner
                 .get(INCLUDE)
@@ -133,7 +586,7 @@ pub mod protobuf {
                     InOption::Include(y) => y.clone(),
                     _ => unreachable!(),
                 })
-                .unwrap_or(Vec::new())
+                .unwrap_or_default()
         }
 
         pub fn get_input(&self) -> Vec<String> {
@@ -143,7 +596,7 @@ pub mod protobuf {
                     InOption::Input(y) => y.clone(),
                     _ => unreachable!(),
                 })
-                .unwrap_or(Vec::new())
+                .unwrap_or_default()
         }
 
         pub fn get_message(&self) -> Option<String> {
@@ -152,14 +605,237 @@ pub mod protobuf {
                 _ => unreachable!(),
             })
         }
+
+        pub fn get_raw(&self) -> bool {
+            self.inner
+                .get(RAW)
+                .map(|x| match x {
+                    InOption::Raw(y) => *y,
+                    _ => unreachable!(),
+                })
+                .unwrap_or(false)
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     impl Options<InOption> for InOptions {
         fn set(&mut self, option: InOption) {
             let name = match option {
+                InOption::DescriptorSet(_) => DESCRIPTOR_SET,
                 InOption::Include(_) => INCLUDE,
                 InOption::Input(_) => INPUT,
                 InOption::Message(_) => MESSAGE,
+                InOption::Raw(_) => RAW,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod xml {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const ATTRIBUTE_PREFIX: &str = "attribute_prefix";
+    const TEXT_KEY: &str = "text_key";
+    const ARRAY: &str = "array";
+    const STRIP_NAMESPACES: &str = "strip_namespaces";
+    const ROOT: &str = "root";
+
+    /// Option of reading XML.
+    #[derive(Debug)]
+    pub enum InOption {
+        /// Prefix of keys that hold attributes, `@` by default.
+        AttributePrefix(String),
+        /// Key that holds text of elements with attributes or children, `#text`
+        /// by default.
+        TextKey(String),
+        /// Names of elements that are always read as arrays.
+        Array(Vec<String>),
+        /// Remove namespace prefixes and declarations.
+        StripNamespaces(bool),
+    }
+
+    /// Set options, see [`InOption`].
+    pub struct InOptions {
+        inner: HashMap<String, InOption>,
+    }
+
+    impl InOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_attribute_prefix(&self) -> Option<&str> {
+            self.inner.get(ATTRIBUTE_PREFIX).map(|x| match x {
+                InOption::AttributePrefix(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+
+        pub fn get_text_key(&self) -> Option<&str> {
+            self.inner.get(TEXT_KEY).map(|x| match x {
+                InOption::TextKey(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+
+        pub fn get_array(&self) -> Vec<String> {
+            self.inner
+                .get(ARRAY)
+                .map(|x| match x {
+                    InOption::Array(x) => x.clone(),
+                    _ => unreachable!(),
+                })
+                .unwrap_or_default()
+        }
+
+        pub fn get_strip_namespaces(&self) -> bool {
+            self.inner
+                .get(STRIP_NAMESPACES)
+                .map(|x| match x {
+                    InOption::StripNamespaces(x) => *x,
+                    _ => unreachable!(),
+                })
+                .unwrap_or(false)
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<InOption> for InOptions {
+        fn set(&mut self, option: InOption) {
+            let name = match option {
+                InOption::AttributePrefix(_) => ATTRIBUTE_PREFIX,
+                InOption::TextKey(_) => TEXT_KEY,
+                InOption::Array(_) => ARRAY,
+                InOption::StripNamespaces(_) => STRIP_NAMESPACES,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+
+    /// Option of writing XML.
+    #[derive(Debug)]
+    pub enum OutOption {
+        /// Prefix of keys that hold attributes, `@` by default.
+        AttributePrefix(String),
+        /// Key that holds text of elements with attributes or children, `#text`
+        /// by default.
+        TextKey(String),
+        /// Name of the root element if document does not have single root, `root`
+        /// by default.
+        Root(String),
+    }
+
+    /// Set options, see [`OutOption`].
+    pub struct OutOptions {
+        inner: HashMap<String, OutOption>,
+    }
+
+    impl OutOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_attribute_prefix(&self) -> Option<&str> {
+            self.inner.get(ATTRIBUTE_PREFIX).map(|x| match x {
+                OutOption::AttributePrefix(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+
+        pub fn get_text_key(&self) -> Option<&str> {
+            self.inner.get(TEXT_KEY).map(|x| match x {
+                OutOption::TextKey(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+
+        pub fn get_root(&self) -> Option<&str> {
+            self.inner.get(ROOT).map(|x| match x {
+                OutOption::Root(x) => x.as_str(),
+                _ => unreachable!(),
+            })
+        }
+    }
+
+    impl Default for OutOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<OutOption> for OutOptions {
+        fn set(&mut self, option: OutOption) {
+            let name = match option {
+                OutOption::AttributePrefix(_) => ATTRIBUTE_PREFIX,
+                OutOption::TextKey(_) => TEXT_KEY,
+                OutOption::Root(_) => ROOT,
+            };
+            self.inner.insert(name.to_string(), option);
+        }
+    }
+}
+
+pub mod yaml {
+    use std::collections::HashMap;
+
+    use crate::options::Options;
+
+    const DOCUMENT: &str = "document";
+
+    /// Option of reading YAML.
+    #[derive(Debug)]
+    pub enum InOption {
+        /// Index of the only document to read from multi-document stream, not
+        /// counting empty documents.
+        Document(usize),
+    }
+
+    /// Set options, see [`InOption`].
+    pub struct InOptions {
+        inner: HashMap<String, InOption>,
+    }
+
+    impl InOptions {
+        pub fn new() -> Self {
+            Self {
+                inner: HashMap::new(),
+            }
+        }
+
+        pub fn get_document(&self) -> Option<usize> {
+            self.inner.get(DOCUMENT).map(|x| match x {
+                InOption::Document(x) => *x,
+            })
+        }
+    }
+
+    impl Default for InOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Options<InOption> for InOptions {
+        fn set(&mut self, option: InOption) {
+            let name = match option {
+                InOption::Document(_) => DOCUMENT,
             };
             self.inner.insert(name.to_string(), option);
         }
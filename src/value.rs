@@ -0,0 +1,327 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use base64::prelude::*;
+use indexmap::IndexMap;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+};
+
+/// Key that holds the value of a flat key which is also a parent of other
+/// keys: `a=1` and `a.b=2` are unflattened into
+/// `{"a": {"#value": "1", "b": "2"}}` and flattened back the same way.
+pub const PARENT_VALUE_KEY: &str = "#value";
+
+/// Format-independent document that every reader produces and every writer consumes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(String),
+    /// RFC 3339 date, time or datetime.
+    Datetime(String),
+    /// Binary data, represented as base64 string by text formats.
+    Bytes(Vec<u8>),
+    Array(Vec<Value>),
+    Object(IndexMap<String, Value>),
+}
+
+impl Value {
+    /// Human readable name of the value type.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Datetime(_) => "datetime",
+            Value::Bytes(_) => "bytes",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// String representation of scalar values, `None` for null, arrays and objects.
+    pub fn to_scalar_string(&self) -> Option<String> {
+        match self {
+            Value::Bool(x) => Some(x.to_string()),
+            Value::Integer(x) => Some(x.to_string()),
+            Value::Float(x) => Some(format!("{:?}", x)),
+            Value::String(x) => Some(x.to_string()),
+            Value::Datetime(x) => Some(x.to_string()),
+            Value::Bytes(x) => Some(BASE64_STANDARD.encode(x)),
+            Value::Null | Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+
+    /// Flattens nested objects into `separator` joined keys. Arrays of scalars
+    /// are joined by comma and nulls are skipped.
+    pub fn flatten(&self, separator: &str) -> Result<IndexMap<String, String>, anyhow::Error> {
+        self.flatten_with(separator, |x| x.to_string())
+    }
+
+    /// Same as [`Value::flatten`] but every key segment is passed through
+    /// `transform_key` before joining. Fails if several keys end up the same.
+    pub fn flatten_with(
+        &self,
+        separator: &str,
+        transform_key: impl Fn(&str) -> String,
+    ) -> Result<IndexMap<String, String>, anyhow::Error> {
+        fn insert(
+            flat: &mut IndexMap<String, String>,
+            key: &str,
+            value: String,
+        ) -> Result<(), anyhow::Error> {
+            if flat.insert(key.to_string(), value).is_some() {
+                return Err(anyhow!(
+                    "Several keys are flattened into the same key {}",
+                    key
+                ));
+            }
+            Ok(())
+        }
+
+        fn traverse(
+            flat: &mut IndexMap<String, String>,
+            key: Option<&str>,
+            separator: &str,
+            transform_key: &dyn Fn(&str) -> String,
+            value: &Value,
+        ) -> Result<(), anyhow::Error> {
+            match value {
+                Value::Null => {}
+                Value::Array(x) => {
+                    let Some(key) = key else {
+                        return Err(anyhow!("Expected to have key defined but have raw array"));
+                    };
+                    let mut result: Vec<String> = Vec::new();
+                    for value in x {
+                        match value.to_scalar_string() {
+                            Some(value) => result.push(value),
+                            None => {
+                                return Err(anyhow!(
+                                    "Array under key {} cannot contain {} values",
+                                    key,
+                                    value.kind()
+                                ));
+                            }
+                        }
+                    }
+                    insert(flat, key, result.join(","))?;
+                }
+                Value::Object(x) => {
+                    for (k, v) in x {
+                        let key = match key {
+                            Some(key) if k == PARENT_VALUE_KEY => key.to_string(),
+                            Some(key) => format!("{}{}{}", key, separator, transform_key(k)),
+                            None => transform_key(k),
+                        };
+                        traverse(flat, Some(key.as_str()), separator, transform_key, v)?;
+                    }
+                }
+                x => {
+                    let Some(key) = key else {
+                        return Err(anyhow!(
+                            "Expected to have key defined but have raw {}",
+                            x.kind()
+                        ));
+                    };
+                    insert(flat, key, x.to_scalar_string().unwrap())?;
+                }
+            }
+            Ok(())
+        }
+
+        let mut flat = IndexMap::new();
+        traverse(&mut flat, None, separator, &transform_key, self)?;
+        Ok(flat)
+    }
+
+    /// Splits keys by `separator` into nested objects of string values. Value
+    /// of a key that is also a parent of other keys is kept under
+    /// [`PARENT_VALUE_KEY`].
+    pub fn unflatten(
+        flat: impl IntoIterator<Item = (String, String)>,
+        separator: &str,
+    ) -> Result<Value, anyhow::Error> {
+        fn insert(object: &mut IndexMap<String, Value>, path: &[&str], value: String) {
+            let (head, tail) = path.split_first().unwrap();
+            if tail.is_empty() {
+                match object.get_mut(*head) {
+                    Some(Value::Object(x)) => {
+                        x.insert(PARENT_VALUE_KEY.to_string(), Value::String(value));
+                    }
+                    _ => {
+                        object.insert(head.to_string(), Value::String(value));
+                    }
+                }
+                return;
+            }
+            let child = object
+                .entry(head.to_string())
+                .or_insert_with(|| Value::Object(IndexMap::new()));
+            if let Value::String(_) = child {
+                let parent_value = std::mem::replace(child, Value::Null);
+                *child = Value::Object(IndexMap::from([(
+                    PARENT_VALUE_KEY.to_string(),
+                    parent_value,
+                )]));
+            }
+            match child {
+                Value::Object(x) => insert(x, tail, value),
+                _ => unreachable!(),
+            }
+        }
+
+        let mut object = IndexMap::new();
+        for (key, value) in flat {
+            let path: Vec<&str> = key.split(separator).collect();
+            insert(&mut object, &path, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(x) => serializer.serialize_bool(*x),
+            Value::Integer(x) => {
+                if let Ok(x) = i64::try_from(*x) {
+                    serializer.serialize_i64(x)
+                } else if let Ok(x) = u64::try_from(*x) {
+                    serializer.serialize_u64(x)
+                } else {
+                    serializer.serialize_i128(*x)
+                }
+            }
+            Value::Float(x) => serializer.serialize_f64(*x),
+            Value::String(x) => serializer.serialize_str(x),
+            Value::Datetime(x) => serializer.serialize_str(x),
+            Value::Bytes(x) if serializer.is_human_readable() => {
+                serializer.serialize_str(&BASE64_STANDARD.encode(x))
+            }
+            Value::Bytes(x) => serializer.serialize_bytes(x),
+            Value::Array(x) => {
+                let mut seq = serializer.serialize_seq(Some(x.len()))?;
+                for value in x {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Object(x) => {
+                let mut map = serializer.serialize_map(Some(x.len()))?;
+                for (k, v) in x {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any structured value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v as i128))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Integer(v as i128))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Value, E> {
+        i128::try_from(v)
+            .map(Value::Integer)
+            .map_err(|_| E::custom("integer is too large"))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut result = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            result.push(value);
+        }
+        Ok(Value::Array(result))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut result = IndexMap::new();
+        while let Some(key) = map.next_key::<Value>()? {
+            let key = key.to_scalar_string().ok_or_else(|| {
+                de::Error::custom(format!("{} cannot be used as a key", key.kind()))
+            })?;
+            result.insert(key, map.next_value()?);
+        }
+        Ok(Value::Object(result))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+        let (_, variant) = data.variant::<de::IgnoredAny>()?;
+        variant.newtype_variant()
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Parses document from JSON text, shortcut for tests.
+#[cfg(test)]
+pub(crate) fn json(json: &str) -> Value {
+    serde_json::from_str(json).unwrap()
+}